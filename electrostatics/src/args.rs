@@ -0,0 +1,43 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+pub struct Args {
+    pub scene: Option<PathBuf>,
+    /// Draw the scene on the CPU to this PNG or SVG file and exit.
+    pub render: Option<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        let mut out = Self {
+            scene: None,
+            render: None,
+            width: 1920,
+            height: 1080,
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing value for `{arg}`"))
+            };
+            match arg.as_str() {
+                "--scene" => out.scene = Some(value()?.into()),
+                "--render" => out.render = Some(value()?.into()),
+                "--width" => out.width = value()?.parse()?,
+                "--height" => out.height = value()?.parse()?,
+                _ => bail!("Unknown argument `{arg}`"),
+            }
+        }
+
+        if out.width == 0 || out.height == 0 {
+            bail!("Width and height must be non-zero");
+        }
+
+        Ok(out)
+    }
+}
@@ -0,0 +1,128 @@
+//! Polylines along which a function sampled on a grid crosses zero, found
+//! by marching squares.
+
+use std::collections::HashMap;
+
+use compute::export::nalgebra::Vector2;
+
+/// A crossing is identified by the grid edge it lies on, the cell corner the
+/// edge starts from and whether it runs along x or y.
+type Edge = (usize, usize, bool);
+
+/// Zero crossings of `f` sampled every `cell` over `0..size`, joined into
+/// polylines. Non-finite samples are treated as gaps.
+pub fn contour(
+    size: Vector2<f32>,
+    cell: f32,
+    f: impl Fn(Vector2<f32>) -> f32,
+) -> Vec<Vec<Vector2<f32>>> {
+    let count = (size / cell).map(|x| x.ceil() as usize + 1);
+    let point = |i: usize, j: usize| Vector2::new(i as f32, j as f32) * cell;
+    let values = (0..count.y)
+        .flat_map(|j| (0..count.x).map(move |i| (i, j)))
+        .map(|(i, j)| f(point(i, j)))
+        .collect::<Vec<_>>();
+    let value = |i: usize, j: usize| values[j * count.x + i];
+
+    let crossing = |(i, j, along_x): Edge| {
+        let (a, b) = if along_x {
+            ((i, j), (i + 1, j))
+        } else {
+            ((i, j), (i, j + 1))
+        };
+        let (va, vb) = (value(a.0, a.1), value(b.0, b.1));
+        if !va.is_finite() || !vb.is_finite() || (va < 0.0) == (vb < 0.0) {
+            return None;
+        }
+
+        let t = va / (va - vb);
+        Some(point(a.0, a.1).lerp(&point(b.0, b.1), t))
+    };
+
+    let mut points = HashMap::new();
+    let mut segments = Vec::new();
+    for j in 0..count.y - 1 {
+        for i in 0..count.x - 1 {
+            let edges = [
+                (i, j, true),
+                (i + 1, j, false),
+                (i, j + 1, true),
+                (i, j, false),
+            ];
+            let crossed = edges
+                .into_iter()
+                .filter(|&edge| match crossing(edge) {
+                    Some(point) => {
+                        points.insert(edge, point);
+                        true
+                    }
+                    None => false,
+                })
+                .collect::<Vec<_>>();
+
+            // Saddles have four crossings, either pairing is as good.
+            for pair in crossed.chunks_exact(2) {
+                segments.push([pair[0], pair[1]]);
+            }
+        }
+    }
+
+    join(&segments)
+        .into_iter()
+        .map(|line| line.iter().map(|x| points[x]).collect())
+        .collect()
+}
+
+/// Chains segments sharing an edge into polylines.
+fn join(segments: &[[Edge; 2]]) -> Vec<Vec<Edge>> {
+    let mut adjacent = HashMap::<Edge, Vec<usize>>::new();
+    for (i, segment) in segments.iter().enumerate() {
+        for edge in segment {
+            adjacent.entry(*edge).or_default().push(i);
+        }
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+    for i in 0..segments.len() {
+        if used[i] {
+            continue;
+        }
+        used[i] = true;
+
+        let mut line = segments[i].to_vec();
+        for _ in 0..2 {
+            while let Some(&next) = adjacent[line.last().unwrap()].iter().find(|&&x| !used[x]) {
+                used[next] = true;
+                let [a, b] = segments[next];
+                line.push(if a == *line.last().unwrap() { b } else { a });
+            }
+            line.reverse();
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::contour;
+
+    #[test]
+    fn circle_is_one_closed_line() {
+        let center = Vector2::new(50.0, 40.0);
+        let lines = contour(Vector2::new(100.0, 80.0), 2.0, |x| {
+            (x - center).magnitude() - 30.0
+        });
+
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line.first(), line.last());
+        for point in line {
+            assert!(((point - center).magnitude() - 30.0).abs() < 0.1);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! CPU evaluation of the complex potential `f(z) = Σ s ln(z - zᵢ)` drawn by
+//! `shader.wgsl`. Field lines are level sets of the stream function `Im f`
+//! where `cos(n Im f)` crosses zero, with `n` from `line_density`, and
+//! equipotentials likewise of `Re f` with `n = v_solutions`.
+//!
+//! `Im f` jumps by `2π q` along a branch cut running in -x from each charge.
+//! The density is chosen so that jump moves every line onto another, which
+//! hides the cuts. When the net charge `Q` is not zero `Im f` also winds by
+//! `2π Q` around the whole scene, and the `2 n |Q|` lines it accounts for
+//! leave the window rather than ending on a charge. Far away the lines look
+//! like those of a single charge `Q`.
+
+use std::{
+    f32::consts::{PI, TAU},
+    iter::Sum,
+    ops::Add,
+};
+
+use compute::export::nalgebra::Vector2;
+
+use crate::types::{Particle, Uniform};
+
+/// Points sampled around a charge by `lines_leaving`.
+const SAMPLES: usize = 4096;
+/// Charges, and differences between them, smaller than this are treated as
+/// zero when looking for a common quantum.
+const CHARGE_TOLERANCE: f32 = 1e-3;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// `-Re f`, falling away from positive charges.
+    pub potential: f32,
+    /// `Im f`, constant along field lines.
+    pub stream: f32,
+    /// `-∇ potential`, pointing away from positive charges.
+    pub field: Vector2<f32>,
+}
+
+/// Evaluates the complex potential at `pos`, with offsets from each charge
+/// scaled componentwise by `metric` first.
+pub fn evaluate(particles: &[Particle], pos: Vector2<f32>, metric: Vector2<f32>) -> Sample {
+    particles.iter().map(|x| term(x, pos, metric)).sum()
+}
+
+/// Evaluates the complex potential at a world position. Like the shader,
+/// offsets are measured in pixels so the lines match what is drawn.
+pub fn sample(ctx: &Uniform, pos: Vector2<f32>) -> Sample {
+    evaluate(&ctx.particles, pos, ctx.window.map(|x| x as f32))
+}
+
+/// Contribution of one charge, `s ln(z - zᵢ)` and its derivative.
+fn term(particle: &Particle, pos: Vector2<f32>, metric: Vector2<f32>) -> Sample {
+    let delta = (pos - particle.position).component_mul(&metric);
+    let dist = delta.norm_squared();
+    Sample {
+        potential: -particle.charge * dist.ln() / 2.0,
+        stream: particle.charge * delta.y.atan2(delta.x),
+        field: delta * particle.charge / dist,
+    }
+}
+
+/// Field lines per unit of `Im f`. This is `e_solutions` rounded so every
+/// charge carries a whole number `2 n |q|` of lines, which needs all charges
+/// to be multiples of a common quantum. Without a quantum giving at least
+/// one line per quantum `e_solutions` is used as is, and lines crossing a
+/// cut are offset along it.
+pub fn line_density(ctx: &Uniform) -> f32 {
+    let n = ctx.e_solutions as f32;
+    let quantum = (ctx.particles.iter())
+        .map(|x| x.charge.abs())
+        .filter(|x| *x > CHARGE_TOLERANCE)
+        .reduce(gcd);
+
+    let Some(quantum) = quantum else {
+        return n;
+    };
+    let lines = (2.0 * quantum * n).round();
+    if lines < 1.0 {
+        return n;
+    }
+    lines / (2.0 * quantum)
+}
+
+/// Euclid's algorithm to within `CHARGE_TOLERANCE`.
+fn gcd(a: f32, b: f32) -> f32 {
+    let (mut a, mut b) = (a.max(b), a.min(b));
+    while b > CHARGE_TOLERANCE {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Number of drawn field lines leaving charge `i`, counted on a small circle
+/// around it. Its own angle is unwrapped so the circle is traversed
+/// continuously, the other charges must not have a branch cut through it.
+pub fn lines_leaving(ctx: &Uniform, i: usize) -> u32 {
+    let window = ctx.window.map(|x| x as f32);
+    let particle = &ctx.particles[i];
+    let center = particle.position.component_mul(&window);
+    let radius = 1e-3 * window.min();
+    let n = line_density(ctx);
+
+    let level = |theta: f32| {
+        let offset = Vector2::new(theta.cos(), theta.sin()) * radius;
+        let pos = (center + offset).component_div(&window);
+        let others = (ctx.particles.iter().enumerate())
+            .filter(|(j, _)| *j != i)
+            .map(|(_, x)| term(x, pos, window).stream)
+            .sum::<f32>();
+        let psi = particle.charge * theta + others;
+        (psi * n / PI - 0.5).floor()
+    };
+
+    let mut lines = 0.0;
+    let mut last = level(-PI);
+    for i in 1..=SAMPLES {
+        let next = level(-PI + TAU * i as f32 / SAMPLES as f32);
+        lines += (next - last).abs();
+        last = next;
+    }
+    lines as u32
+}
+
+impl Add for Sample {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            potential: self.potential + rhs.potential,
+            stream: self.stream + rhs.stream,
+            field: self.field + rhs.field,
+        }
+    }
+}
+
+impl Sum for Sample {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use compute::export::nalgebra::Vector2;
+
+    use super::{evaluate, line_density, lines_leaving, sample};
+    use crate::types::{Particle, Uniform};
+
+    const UNIT: Vector2<f32> = Vector2::new(1.0, 1.0);
+
+    fn particle(charge: f32, x: f32, y: f32) -> Particle {
+        Particle {
+            charge,
+            position: Vector2::new(x, y),
+        }
+    }
+
+    #[test]
+    fn coulomb_field() {
+        // In two dimensions a point charge s has E = s / r, radially outwards,
+        // and potential -s ln r.
+        for charge in [1.0, -2.5, 0.3] {
+            let particles = [particle(charge, 0.2, -0.1)];
+            for [x, y] in [[1.0, 0.0], [0.5, 0.4], [-0.3, -0.8]] {
+                let offset = Vector2::new(x, y);
+                let r = offset.magnitude();
+                let sample = evaluate(&particles, particles[0].position + offset, UNIT);
+
+                let expected = offset / r * charge / r;
+                assert!((sample.field - expected).magnitude() < 1e-5, "{sample:?}");
+                assert!((sample.potential + charge * r.ln()).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn superposition_and_gradient() {
+        let particles = [
+            particle(1.0, 0.3, 0.5),
+            particle(-1.5, 0.7, 0.4),
+            particle(0.5, 0.5, 0.8),
+        ];
+        let h = 1e-3;
+        for [x, y] in [[0.5, 0.5], [0.1, 0.2], [0.9, 0.9]] {
+            let pos = Vector2::new(x, y);
+            let sample = evaluate(&particles, pos, UNIT);
+
+            let sum = particles
+                .iter()
+                .map(|p| evaluate(std::slice::from_ref(p), pos, UNIT).field)
+                .sum::<Vector2<f32>>();
+            assert!((sample.field - sum).magnitude() < 1e-5);
+
+            // E = -∇V, and the stream function is constant along E.
+            let at = |d: Vector2<f32>| evaluate(&particles, pos + d, UNIT);
+            let gradient = |f: fn(&super::Sample) -> f32| {
+                Vector2::new(
+                    f(&at(Vector2::new(h, 0.0))) - f(&at(Vector2::new(-h, 0.0))),
+                    f(&at(Vector2::new(0.0, h))) - f(&at(Vector2::new(0.0, -h))),
+                ) / (2.0 * h)
+            };
+            let potential = gradient(|x| x.potential);
+            let stream = gradient(|x| x.stream);
+            let tolerance = 1e-2 * sample.field.magnitude();
+            assert!(
+                (sample.field + potential).magnitude() < tolerance,
+                "{sample:?}"
+            );
+            assert!(stream.dot(&sample.field).abs() < tolerance * stream.magnitude());
+        }
+    }
+
+    #[test]
+    fn stream_winds_by_charge() {
+        let ctx = Uniform {
+            window: Vector2::new(800, 600),
+            particles: vec![particle(1.5, 0.5, 0.5)],
+            ..Default::default()
+        };
+
+        // Just either side of the branch cut in -x.
+        let above = sample(&ctx, Vector2::new(0.4, 0.5 + 1e-4)).stream;
+        let below = sample(&ctx, Vector2::new(0.4, 0.5 - 1e-4)).stream;
+        assert!((above - below - 1.5 * TAU).abs() < 1e-2, "{above} {below}");
+    }
+
+    #[test]
+    fn whole_lines_per_charge() {
+        let ctx = |charges: &[f32]| Uniform {
+            e_solutions: 5,
+            particles: (charges.iter())
+                .map(|&charge| particle(charge, 0.5, 0.5))
+                .collect(),
+            ..Default::default()
+        };
+
+        for charges in [&[1.0, -1.0][..], &[0.35, -0.7], &[0.25, 0.5, -1.5], &[0.4]] {
+            let n = line_density(&ctx(charges));
+            for charge in charges {
+                let lines = 2.0 * n * charge.abs();
+                assert!((lines - lines.round()).abs() < 1e-3, "{charges:?}");
+            }
+            assert!((n - 5.0).abs() < 5.0 / 4.0, "{charges:?}: {n}");
+        }
+
+        // No common quantum coarse enough for a line, so left unchanged.
+        assert_eq!(line_density(&ctx(&[1.0, -1.0007])), 5.0);
+        assert_eq!(line_density(&ctx(&[])), 5.0);
+    }
+
+    #[test]
+    fn branch_cuts_hidden() {
+        // At the plain density of 5 the smaller charge carries 3.5 lines.
+        let ctx = Uniform {
+            window: Vector2::new(800, 600),
+            e_solutions: 5,
+            particles: vec![particle(0.35, 0.3, 0.3), particle(-0.7, 0.7, 0.7)],
+            ..Default::default()
+        };
+        let n = line_density(&ctx);
+
+        // Lines are drawn where cos(n ψ) is zero, so the pattern only
+        // continues across a cut if |cos(n ψ)| does. Each cut runs in -x
+        // from its charge, clear of the other one, and is sampled outside
+        // the marker.
+        for particle in &ctx.particles {
+            for i in 0..100 {
+                let x = (particle.position.x - 0.1) * i as f32 / 100.0;
+                let at = |dy: f32| {
+                    let pos = Vector2::new(x, particle.position.y + dy);
+                    (sample(&ctx, pos).stream * n).cos().abs()
+                };
+                let (above, below) = (at(1e-6), at(-1e-6));
+                assert!((above - below).abs() < 1e-2, "{x}: {above} {below}");
+            }
+        }
+    }
+
+    #[test]
+    fn line_count_proportional_to_charge() {
+        for [a, b] in [[1.0, -1.0], [1.0, 1.5], [0.5, -2.0], [3.0, 0.2]] {
+            // Diagonal so neither branch cut, which runs in -x from each
+            // charge, passes the other.
+            let ctx = Uniform {
+                window: Vector2::new(800, 600),
+                scale: 1.0,
+                e_solutions: 10,
+                particles: vec![
+                    Particle {
+                        charge: a,
+                        position: Vector2::new(0.3, 0.3),
+                    },
+                    Particle {
+                        charge: b,
+                        position: Vector2::new(0.7, 0.7),
+                    },
+                ],
+                ..Default::default()
+            };
+
+            // Around a full turn ψ changes by 2π q, crossing 2 n |q| levels.
+            for (i, charge) in [a, b].into_iter().enumerate() {
+                let expected = 2.0 * ctx.e_solutions as f32 * charge.abs();
+                assert_eq!(
+                    lines_leaving(&ctx, i),
+                    expected as u32,
+                    "{a}:{b} charge {i}"
+                );
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, path::Path};
 
 use anyhow::Result;
 use compute::{
     buffer::StorageBuffer,
     export::{
-        egui::{self, emath::Numeric, Context, DragValue, Slider, Ui},
+        egui::{
+            self, emath::Numeric, Button, CentralPanel, CollapsingHeader, Color32, ComboBox,
+            Context, DragValue, Frame, Grid, Id, Pos2, Sense, Shape, Slider, Stroke, TextEdit, Ui,
+        },
         nalgebra::Vector2,
         wgpu::{include_wgsl, RenderPass, ShaderStages},
         winit::window::WindowAttributes,
@@ -14,32 +17,48 @@ use compute::{
     misc::mutability::Immutable,
     pipeline::render::RenderPipeline,
 };
-use encase::ShaderType;
+
+mod args;
+mod contour;
+mod field;
+mod probe;
+mod render;
+mod scene;
+mod svg;
+mod trace;
+mod trajectory;
+mod types;
+mod view;
+use crate::{
+    args::Args,
+    probe::{ChargeUnit, Reading, Units},
+    scene::Classic,
+    trajectory::{Integrator, Trajectory},
+    types::{Particle, Uniform},
+};
+
+/// How far a duplicated charge is moved so it does not hide the original.
+const DUPLICATE_OFFSET: f32 = 0.05;
+/// Width of the test charge's trail and radius of its marker, in points.
+const TRAIL_WIDTH: f32 = 2.0;
+const BODY_RADIUS: f32 = 4.0;
 
 struct App {
     uniform: StorageBuffer<Uniform, Immutable>,
     render: RenderPipeline,
 
     ctx: Uniform,
-}
-
-#[derive(ShaderType, Default)]
-struct Uniform {
-    window: Vector2<u32>,
-    scale: f32,
-    position: Vector2<f32>,
-
-    e_solutions: u32,
-    v_solutions: u32,
-
-    #[size(runtime)]
-    particles: Vec<Particle>,
-}
-
-#[derive(ShaderType, Default, Clone)]
-struct Particle {
-    charge: i32,
-    position: Vector2<f32>,
+    scene_path: String,
+    /// Charge being dragged and its offset from the pointer.
+    dragging: Option<(usize, Vector2<f32>)>,
+    /// Charge the open context menu acts on.
+    context: Option<usize>,
+    /// Field under the pointer, outside of any charge.
+    probe: Option<Reading>,
+    units: Units,
+    /// In `units.charge`.
+    test_charge: f32,
+    trajectory: Trajectory,
 }
 
 impl Interactive for App {
@@ -47,51 +66,19 @@ impl Interactive for App {
         let screen = gcx.window.inner_size();
         self.ctx.window = Vector2::new(screen.width, screen.height);
 
+        self.ctx.e_density = field::line_density(&self.ctx);
         self.uniform.upload(&self.ctx).unwrap();
         self.render.draw_quad(render_pass, 0..1);
     }
 
     fn ui(&mut self, gcx: GraphicsCtx, ctx: &Context) {
-        let dragging_viewport = ctx.dragged_id().is_none();
-
         let window = gcx.window.inner_size();
-        let scale = window.width.min(window.height) as f32;
-
-        ctx.input_mut(|input| {
-            let old_scale = self.ctx.scale;
-            self.ctx.scale += input.smooth_scroll_delta.y / 200.0;
-
-            if input.pointer.any_down() && dragging_viewport {
-                let delta = input.pointer.delta();
-                self.ctx.position += Vector2::new(-delta.x, delta.y);
-            }
+        self.ctx.window = Vector2::new(window.width, window.height);
 
-            let Some(pointer) = input.pointer.latest_pos() else {
-                return;
-            };
-
-            let pointer = (Vector2::new(
-                pointer.x / window.width as f32,
-                1.0 - pointer.y / window.height as f32,
-            ) - Vector2::repeat(0.5)
-                + self.ctx.position / scale)
-                / self.ctx.scale
-                + Vector2::repeat(0.5);
-
-            self.ctx.position +=
-                (pointer - self.ctx.position) * (old_scale - self.ctx.scale) / old_scale;
-
-            self.ctx
-                .particles
-                .retain(|x| (x.position - pointer).magnitude() > 0.1);
-
-            // if input.consume_key(Modifiers::NONE, Key::Plus) {
-            //     self.ctx.particles.push(Particle {
-            //         charge: 2,
-            //         position: pointer,
-            //     });
-            // }
-        });
+        self.trajectory.advance(&self.ctx);
+        CentralPanel::default()
+            .frame(Frame::none())
+            .show(ctx, |ui| self.scene(ui));
 
         egui::Window::new("Electrostatics")
             .max_width(0.0)
@@ -110,8 +97,426 @@ impl Interactive for App {
 
                 dragger(ui, "E Solutions", &mut self.ctx.e_solutions, 0..=10);
                 dragger(ui, "V Solutions", &mut self.ctx.v_solutions, 0..=10);
+                color_editor(ui, &mut self.ctx);
+
+                ui.separator();
+
+                self.probe_editor(ui);
+
+                ui.separator();
+
+                trajectory_editor(ui, &mut self.trajectory, &self.ctx);
+
+                ui.separator();
+
+                self.scene_editor(ui);
+
+                ui.separator();
+
+                let center = self.ctx.window.map(|x| x as f32) / 2.0;
+                let center = view::screen_to_world(&self.ctx, center);
+                if charge_editor(ui, &mut self.ctx.particles, center) {
+                    // Indices may have shifted under an open drag or menu.
+                    self.dragging = None;
+                    self.context = None;
+                }
+            });
+    }
+}
+
+impl App {
+    fn probe_editor(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Probe")
+            .default_open(true)
+            .show(ui, |ui| {
+                let unit = self.units.charge.name();
+                ComboBox::from_label("Charge Unit")
+                    .selected_text(unit)
+                    .show_ui(ui, |ui| {
+                        for charge in ChargeUnit::ALL {
+                            ui.selectable_value(&mut self.units.charge, charge, charge.name());
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    let size = DragValue::new(&mut self.units.size)
+                        .speed(0.01)
+                        .suffix(" m");
+                    ui.add(size.range(1e-6..=f32::MAX));
+                    ui.label("Window Size");
+                });
+                ui.horizontal(|ui| {
+                    ui.add(
+                        DragValue::new(&mut self.test_charge)
+                            .speed(0.1)
+                            .suffix(format!(" {unit}")),
+                    );
+                    ui.label("Test Charge");
+                });
+
+                let Some(probe) = self.probe else {
+                    ui.label("Hover the plot to probe the field.");
+                    return;
+                };
+
+                // World y points up, so the angle is counterclockwise from +x.
+                let angle = |x: Vector2<f32>| x.y.atan2(x.x).to_degrees();
+                ui.label(format!("V: {:.3e} V", probe.potential));
+                ui.label(format!(
+                    "E: {:.3e} V/m at {:.0}°",
+                    probe.field.magnitude(),
+                    angle(probe.field)
+                ));
+                ui.label(format!(
+                    "F: {:.3e} N at {:.0}°",
+                    probe.force.magnitude(),
+                    angle(probe.force)
+                ));
             });
     }
+
+    fn scene_editor(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Scene").show(ui, |ui| {
+            ui.add(TextEdit::singleline(&mut self.scene_path).hint_text("Path"));
+            ui.horizontal(|ui| {
+                let path = Path::new(&self.scene_path);
+                if ui.button("Save").clicked() {
+                    if let Err(err) = scene::save(&self.ctx, path) {
+                        eprintln!("Failed to save scene: {err}");
+                    }
+                }
+
+                if ui.button("Open").clicked() {
+                    match scene::load(path) {
+                        Ok(ctx) => self.load(ctx),
+                        Err(err) => eprintln!("Failed to open scene: {err}"),
+                    }
+                }
+
+                // Drawn on the CPU, at the current window size.
+                if ui.button("Render PNG").clicked() {
+                    let path = Path::new(&self.scene_path).with_extension("png");
+                    if let Err(err) = render::render(&self.ctx).save(&path) {
+                        eprintln!("Failed to render {}: {err}", path.display());
+                    }
+                }
+
+                if ui.button("Export SVG").clicked() {
+                    let path = Path::new(&self.scene_path).with_extension("svg");
+                    if let Err(err) = svg::save(&self.ctx, &path) {
+                        eprintln!("Failed to export {}: {err}", path.display());
+                    }
+                }
+            });
+
+            ComboBox::from_label("Library")
+                .selected_text("Choose")
+                .show_ui(ui, |ui| {
+                    for classic in Classic::ALL {
+                        if ui.selectable_label(false, classic.name()).clicked() {
+                            self.ctx.particles = classic.particles();
+                            self.dragging = None;
+                            self.context = None;
+                        }
+                    }
+                });
+        });
+    }
+
+    fn load(&mut self, ctx: Uniform) {
+        self.ctx = Uniform {
+            window: self.ctx.window,
+            ..ctx
+        };
+        self.dragging = None;
+        self.context = None;
+    }
+
+    /// Handles pointer input over the field, everywhere outside the panel.
+    fn scene(&mut self, ui: &mut Ui) {
+        let response = ui.interact(ui.max_rect(), Id::new("scene"), Sense::click_and_drag());
+        let (pixels_per_point, shift, alt, scroll) = ui.input(|input| {
+            (
+                input.pixels_per_point,
+                input.modifiers.shift,
+                input.modifiers.alt,
+                input.smooth_scroll_delta.y,
+            )
+        });
+
+        let pointer = response
+            .interact_pointer_pos()
+            .or(response.hover_pos())
+            .map(|x| view::screen_to_world(&self.ctx, Vector2::new(x.x, x.y) * pixels_per_point));
+        let hovered = pointer.and_then(|x| view::hit(&self.ctx, x));
+        self.probe = pointer
+            .filter(|_| hovered.is_none())
+            .map(|x| probe::probe(&self.ctx, &self.units, x, self.test_charge));
+
+        if response.drag_started() {
+            self.dragging = hovered
+                .zip(pointer)
+                .map(|(i, pointer)| (i, self.ctx.particles[i].position - pointer));
+        }
+
+        if response.dragged() {
+            match (self.dragging, pointer) {
+                (Some((i, offset)), Some(pointer)) => {
+                    self.ctx.particles[i].position = pointer + offset;
+                }
+                _ => {
+                    let delta = response.drag_delta() * pixels_per_point;
+                    self.ctx.position += Vector2::new(-delta.x, delta.y);
+                }
+            }
+        }
+
+        if response.drag_stopped() {
+            self.dragging = None;
+        }
+
+        if response.hovered() {
+            match (hovered, pointer) {
+                // Scrolling over a charge scales its magnitude, keeping the sign.
+                (Some(i), _) => self.ctx.particles[i].charge *= (scroll / 500.0).exp(),
+                (None, Some(pointer)) if scroll != 0.0 => {
+                    let old_scale = self.ctx.scale;
+                    self.ctx.scale += scroll / 200.0;
+                    self.ctx.position +=
+                        (pointer - self.ctx.position) * (old_scale - self.ctx.scale) / old_scale;
+                }
+                _ => {}
+            }
+        }
+
+        if let (true, None, Some(position)) = (response.clicked(), hovered, pointer) {
+            if alt {
+                self.trajectory.launch(position);
+            } else {
+                let charge = if shift { -1.0 } else { 1.0 };
+                self.ctx.particles.push(Particle { charge, position });
+            }
+        }
+
+        if response.secondary_clicked() {
+            self.context = hovered;
+        }
+
+        response.context_menu(|ui| {
+            let Some(i) = self.context else {
+                ui.label("Click to add a positive charge, shift click for a negative one.");
+                ui.label("Alt click to launch a test charge.");
+                return;
+            };
+
+            if ui.button("Delete").clicked() {
+                self.ctx.particles.remove(i);
+                self.context = None;
+                self.dragging = None;
+                ui.close_menu();
+            }
+        });
+
+        let to_screen = |x: &Vector2<f32>| {
+            let screen = view::world_to_screen(&self.ctx, *x) / pixels_per_point;
+            Pos2::new(screen.x, screen.y)
+        };
+        let trail = self
+            .trajectory
+            .trail
+            .iter()
+            .map(to_screen)
+            .collect::<Vec<_>>();
+        if let Some(&last) = trail.last() {
+            let painter = ui.painter();
+            painter.add(Shape::line(trail, Stroke::new(TRAIL_WIDTH, Color32::WHITE)));
+            painter.circle_filled(last, BODY_RADIUS, Color32::WHITE);
+        }
+
+        if let Some(i) = hovered.filter(|_| self.dragging.is_none()) {
+            let charge = self.ctx.particles[i].charge;
+            let lines = field::lines_leaving(&self.ctx, i);
+            response.on_hover_text_at_pointer(format!("Charge {charge:.2}, {lines} field lines"));
+        }
+    }
+}
+
+/// Table of every charge, returns whether any were added or removed. New
+/// charges are placed at `center`, the middle of the view.
+fn charge_editor(ui: &mut Ui, particles: &mut Vec<Particle>, center: Vector2<f32>) -> bool {
+    let mut changed = false;
+
+    CollapsingHeader::new("Charges")
+        .default_open(true)
+        .show(ui, |ui| {
+            let mut remove = None;
+            let mut duplicate = None;
+
+            Grid::new("charges").striped(true).show(ui, |ui| {
+                ui.label("x");
+                ui.label("y");
+                ui.label("Charge");
+                ui.end_row();
+
+                for (i, particle) in particles.iter_mut().enumerate() {
+                    for value in particle.position.iter_mut() {
+                        ui.add(DragValue::new(value).speed(0.001).max_decimals(3));
+                    }
+                    ui.add(
+                        DragValue::new(&mut particle.charge)
+                            .speed(0.05)
+                            .max_decimals(2),
+                    );
+
+                    if ui.button("Duplicate").clicked() {
+                        duplicate = Some(i);
+                    }
+                    if ui.button("Delete").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if let Some(i) = duplicate {
+                let mut particle = particles[i].clone();
+                particle.position.x += DUPLICATE_OFFSET;
+                particles.insert(i + 1, particle);
+                changed = true;
+            }
+
+            if let Some(i) = remove {
+                particles.remove(i);
+                changed = true;
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button("+ Charge").clicked() {
+                    particles.push(Particle {
+                        charge: 1.0,
+                        position: center,
+                    });
+                    changed = true;
+                }
+
+                let net = particles.iter().map(|x| x.charge).sum::<f32>();
+                let neutralise = ui
+                    .add_enabled(net.abs() > 1e-6, Button::new("Neutralise"))
+                    .on_hover_text("Add a charge at the center cancelling the net charge");
+                if neutralise.clicked() {
+                    particles.push(Particle {
+                        charge: -net,
+                        position: center,
+                    });
+                    changed = true;
+                }
+
+                let mirror = ui
+                    .button("Mirror")
+                    .on_hover_text("Add an opposite image of every charge across the center line");
+                if mirror.clicked() {
+                    let images = particles
+                        .iter()
+                        .map(|x| Particle {
+                            charge: -x.charge,
+                            position: Vector2::new(2.0 * center.x - x.position.x, x.position.y),
+                        })
+                        .collect::<Vec<_>>();
+                    particles.extend(images);
+                    changed = true;
+                }
+            });
+        });
+
+    changed
+}
+
+fn color_editor(ui: &mut Ui, ctx: &mut Uniform) {
+    CollapsingHeader::new("Colors").show(ui, |ui| {
+        for (label, color) in [
+            ("Field Lines", &mut ctx.e_color),
+            ("Equipotentials", &mut ctx.v_color),
+            ("Positive", &mut ctx.positive_color),
+            ("Negative", &mut ctx.negative_color),
+        ] {
+            ui.horizontal(|ui| {
+                let mut rgb = (*color).into();
+                ui.color_edit_button_rgb(&mut rgb);
+                *color = rgb.into();
+                ui.label(label);
+            });
+        }
+    });
+}
+
+fn trajectory_editor(ui: &mut Ui, trajectory: &mut Trajectory, ctx: &Uniform) {
+    CollapsingHeader::new("Trajectory").show(ui, |ui| {
+        let launch = &mut trajectory.launch;
+        Grid::new("trajectory").show(ui, |ui| {
+            ui.label("Charge");
+            ui.add(DragValue::new(&mut launch.charge).speed(0.01));
+            ui.end_row();
+
+            ui.label("Mass");
+            ui.add(
+                DragValue::new(&mut launch.mass)
+                    .speed(0.01)
+                    .range(1e-3..=f32::MAX),
+            );
+            ui.end_row();
+
+            ui.label("Velocity");
+            ui.horizontal(|ui| {
+                for value in launch.velocity.iter_mut() {
+                    ui.add(DragValue::new(value).speed(0.01));
+                }
+            });
+            ui.end_row();
+        });
+
+        ComboBox::from_label("Integrator")
+            .selected_text(trajectory.integrator.name())
+            .show_ui(ui, |ui| {
+                for integrator in Integrator::ALL {
+                    let name = integrator.name();
+                    ui.selectable_value(&mut trajectory.integrator, integrator, name);
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut trajectory.dt)
+                    .speed(1e-5)
+                    .range(1e-6..=1.0),
+            );
+            ui.label("Δt");
+        });
+        dragger(ui, "Steps per Frame", &mut trajectory.steps, 1..=100);
+        ui.checkbox(&mut trajectory.collide, "Stop on Charges");
+
+        ui.horizontal(|ui| {
+            if let Some(body) = trajectory.body {
+                if ui.button("Restart").clicked() {
+                    trajectory.launch(trajectory.trail[0]);
+                }
+                if ui.button("Clear").clicked() {
+                    trajectory.clear();
+                }
+                if trajectory.stopped {
+                    ui.label("Stopped");
+                } else {
+                    // Constant up to the integrator's error.
+                    let metric = trajectory::metric(ctx);
+                    let energy = trajectory::energy(&ctx.particles, metric, &body);
+                    ui.label(format!(
+                        "|v| {:.3}, energy {energy:.4}",
+                        body.velocity.magnitude()
+                    ));
+                }
+            } else {
+                ui.label("Alt click the plot to launch a test charge.");
+            }
+        });
+    });
 }
 
 fn dragger<T: Numeric>(ui: &mut Ui, label: &str, value: &mut T, range: RangeInclusive<T>) {
@@ -122,6 +527,29 @@ fn dragger<T: Numeric>(ui: &mut Ui, label: &str, value: &mut T, range: RangeIncl
 }
 
 fn main() -> Result<()> {
+    let args = Args::parse()?;
+    let ctx = match &args.scene {
+        Some(path) => scene::load(path)?,
+        None => Uniform::default(),
+    };
+
+    if let Some(path) = &args.render {
+        let ctx = Uniform {
+            window: Vector2::new(args.width, args.height),
+            ..ctx
+        };
+        match path.extension().and_then(|x| x.to_str()) {
+            Some("svg") => svg::save(&ctx, path)?,
+            _ => render::render(&ctx).save(path)?,
+        }
+        println!(
+            "Rendered {} charges to {}",
+            ctx.particles.len(),
+            path.display()
+        );
+        return Ok(());
+    }
+
     let gpu = Gpu::init()?;
 
     let uniform = gpu
@@ -141,22 +569,16 @@ fn main() -> Result<()> {
             uniform,
             render,
 
-            ctx: Uniform {
-                scale: 1.0,
-                e_solutions: 5,
-                v_solutions: 5,
-                particles: vec![
-                    Particle {
-                        charge: 2,
-                        position: Vector2::new(0.3, 0.5),
-                    },
-                    Particle {
-                        charge: -2,
-                        position: Vector2::new(0.7, 0.5),
-                    },
-                ],
-                ..Default::default()
-            },
+            ctx,
+            scene_path: args
+                .scene
+                .map_or_else(|| "scene.json".into(), |x| x.display().to_string()),
+            dragging: None,
+            context: None,
+            probe: None,
+            units: Units::default(),
+            test_charge: 1.0,
+            trajectory: Trajectory::default(),
         },
     )
     .run()?;
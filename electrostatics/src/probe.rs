@@ -0,0 +1,169 @@
+//! Readout of the potential, field and force on a test charge at a point, in
+//! physical units. The plot is two dimensional, so each charge is taken as
+//! an infinite line charge through the plane, holding its charge per metre,
+//! and potentials are relative to one metre from each charge.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{field, types::Uniform};
+
+/// Coulomb's constant `1 / (4π ε₀)`, in N m² / C².
+pub const COULOMB: f32 = 8.987_552e9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeUnit {
+    Elementary,
+    Nanocoulomb,
+    Microcoulomb,
+    Coulomb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Units {
+    /// Unit of both the scene's charges and the test charge.
+    pub charge: ChargeUnit,
+    /// Metres spanned by the shorter side of the window at a scale of one.
+    pub size: f32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Reading {
+    /// In volts.
+    pub potential: f32,
+    /// In volts per metre.
+    pub field: Vector2<f32>,
+    /// On the test charge, in newtons.
+    pub force: Vector2<f32>,
+}
+
+impl ChargeUnit {
+    pub const ALL: [ChargeUnit; 4] = [
+        ChargeUnit::Elementary,
+        ChargeUnit::Nanocoulomb,
+        ChargeUnit::Microcoulomb,
+        ChargeUnit::Coulomb,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChargeUnit::Elementary => "e",
+            ChargeUnit::Nanocoulomb => "nC",
+            ChargeUnit::Microcoulomb => "μC",
+            ChargeUnit::Coulomb => "C",
+        }
+    }
+
+    pub fn coulombs(&self) -> f32 {
+        match self {
+            ChargeUnit::Elementary => 1.602_176_6e-19,
+            ChargeUnit::Nanocoulomb => 1e-9,
+            ChargeUnit::Microcoulomb => 1e-6,
+            ChargeUnit::Coulomb => 1.0,
+        }
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            charge: ChargeUnit::Nanocoulomb,
+            size: 1.0,
+        }
+    }
+}
+
+/// Reads the field at a world position, with a test charge of `test_charge`
+/// in `units.charge`.
+pub fn probe(ctx: &Uniform, units: &Units, pos: Vector2<f32>, test_charge: f32) -> Reading {
+    let window = ctx.window.map(|x| x as f32);
+    let metric = window / window.min() * units.size;
+    let sample = field::evaluate(&ctx.particles, pos, metric);
+
+    // A line charge λ has E = 2 k λ / r and V = -2 k λ ln r, where the sample
+    // holds λ / r and -λ ln r.
+    let unit = units.charge.coulombs();
+    let field = sample.field * 2.0 * COULOMB * unit;
+    Reading {
+        potential: sample.potential * 2.0 * COULOMB * unit,
+        field,
+        force: field * test_charge * unit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{probe, ChargeUnit, Units, COULOMB};
+    use crate::types::{Particle, Uniform};
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-4 * a.abs().max(b.abs())
+    }
+
+    #[test]
+    fn line_charge() {
+        // The shorter side is 2 m, so a quarter of the width is 1 m.
+        let ctx = Uniform {
+            window: Vector2::new(1000, 500),
+            particles: vec![Particle {
+                charge: 1.0,
+                position: Vector2::new(0.5, 0.5),
+            }],
+            ..Default::default()
+        };
+        let units = Units {
+            charge: ChargeUnit::Nanocoulomb,
+            size: 2.0,
+        };
+        let e = 2.0 * COULOMB * 1e-9;
+
+        let reading = probe(&ctx, &units, Vector2::new(0.75, 0.5), 2.0);
+        assert!(
+            close(reading.field.x, e) && reading.field.y == 0.0,
+            "{reading:?}"
+        );
+        assert_eq!(reading.potential, 0.0);
+        assert!(close(reading.force.x, e * 2e-9), "{reading:?}");
+
+        // Half of the height is also 1 m, and potential falls as -ln r.
+        let reading = probe(&ctx, &units, Vector2::new(0.5, 0.0), 1.0);
+        assert!(
+            close(reading.field.y, -e) && reading.field.x == 0.0,
+            "{reading:?}"
+        );
+        let reading = probe(&ctx, &units, Vector2::new(1.0, 0.5), 1.0);
+        assert!(close(reading.field.x, e / 2.0), "{reading:?}");
+        assert!(close(reading.potential, -e * 2f32.ln()), "{reading:?}");
+    }
+
+    #[test]
+    fn force_follows_sign() {
+        let ctx = Uniform {
+            window: Vector2::new(800, 600),
+            ..Default::default()
+        };
+        let units = Units::default();
+
+        // Between the default dipole's charges the field runs from + to -,
+        // pushing positive test charges the same way and negative ones back.
+        let [positive, negative] = [&ctx.particles[0], &ctx.particles[1]];
+        let mid = (positive.position + negative.position) / 2.0;
+        let towards = (negative.position - positive.position).normalize();
+        for test_charge in [1.0, -1.0] {
+            let reading = probe(&ctx, &units, mid, test_charge);
+            assert!(reading.field.normalize().dot(&towards) > 0.999);
+            assert!(reading.force.normalize().dot(&towards) * test_charge > 0.999);
+        }
+
+        let elementary = Units {
+            charge: ChargeUnit::Elementary,
+            ..units
+        };
+        let a = probe(&ctx, &units, mid, 1.0);
+        let b = probe(&ctx, &elementary, mid, 1.0);
+        let ratio = ChargeUnit::Nanocoulomb.coulombs() / ChargeUnit::Elementary.coulombs();
+        assert!(close(a.field.x / b.field.x, ratio));
+        assert!(close(a.force.x / b.force.x, ratio * ratio));
+    }
+}
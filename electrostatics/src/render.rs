@@ -0,0 +1,98 @@
+//! Software version of `frag` in `shader.wgsl` built on `field::sample`, to
+//! draw scenes without a GPU. Like the shader, line widths come from the
+//! analytic gradient.
+
+use compute::export::nalgebra::{Vector2, Vector3};
+use image::{Rgb, RgbImage};
+
+use crate::{
+    field,
+    types::Uniform,
+    view::{self, PARTICLE_RADIUS},
+};
+
+/// Same as the constants in `shader.wgsl`.
+const FADE_RADIUS: f32 = 0.05;
+const LINE_THICKNESS: f32 = 1.5;
+
+/// Draws the scene at the size of `ctx.window`.
+pub fn render(ctx: &Uniform) -> RgbImage {
+    RgbImage::from_fn(ctx.window.x, ctx.window.y, |x, y| {
+        let screen = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+        let color = shade(ctx, view::screen_to_world(ctx, screen));
+        Rgb(color
+            .map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .into())
+    })
+}
+
+/// Color at a world position, before quantization.
+pub fn shade(ctx: &Uniform, pos: Vector2<f32>) -> Vector3<f32> {
+    let window = ctx.window.map(|x| x as f32);
+    let scale = window.min();
+    let sample = field::sample(ctx, pos);
+
+    // Both the potential and stream function change by |E| per world pixel,
+    // one along E and the other across it. A screen pixel is 1 / scale of
+    // those, and fwidth sums the change along each axis.
+    let gradient = sample.field / ctx.scale;
+    let width = gradient.x.abs() + gradient.y.abs();
+    let line = |value: f32, n: f32| {
+        let fwidth = n * (value * n).sin().abs() * width;
+        (LINE_THICKNESS - ((value * n).cos() / fwidth).abs()).max(0.0)
+    };
+
+    let e = ctx.e_color * line(sample.stream, field::line_density(ctx));
+    let v = ctx.v_color * line(sample.potential, ctx.v_solutions as f32);
+    let mut color = e.sup(&v);
+
+    for particle in &ctx.particles {
+        let delta = (pos - particle.position).component_mul(&window);
+        let fill = if particle.charge < 0.0 {
+            ctx.negative_color
+        } else {
+            ctx.positive_color
+        };
+
+        let t = -(delta.magnitude() - PARTICLE_RADIUS * scale) / (FADE_RADIUS * scale);
+        color = color.lerp(&fill, smoothstep(t));
+    }
+
+    color
+}
+
+fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+    use image::Rgb;
+
+    use super::render;
+    use crate::types::Uniform;
+
+    #[test]
+    fn charges_drawn_in_place() {
+        let ctx = Uniform {
+            window: Vector2::new(200, 100),
+            ..Default::default()
+        };
+        let image = render(&ctx);
+
+        // The default dipole sits on the horizontal center line.
+        for particle in &ctx.particles {
+            let x = (particle.position.x * 200.0) as u32;
+            let y = ((1.0 - particle.position.y) * 100.0) as u32;
+            let expected = if particle.charge < 0.0 {
+                ctx.negative_color
+            } else {
+                ctx.positive_color
+            };
+            let expected = Rgb(expected.map(|x| (x * 255.0).round() as u8).into());
+            assert_eq!(*image.get_pixel(x, y), expected);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Scenes are a `Uniform` saved as pretty printed JSON so they can be edited
+//! by hand, along with a library of classic arrangements to start from.
+
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use compute::export::nalgebra::Vector2;
+
+use crate::types::{Particle, Uniform};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classic {
+    Dipole,
+    Quadrupole,
+    /// A row of like charges.
+    Line,
+    /// Two rows of opposite charges, like the plates of a capacitor.
+    Capacitor,
+}
+
+pub fn save(ctx: &Uniform, path: &Path) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(ctx)?)?;
+    Ok(())
+}
+
+/// Anything missing from the file takes its default, so a scene can be as
+/// little as a list of charges.
+pub fn load(path: &Path) -> Result<Uniform> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+impl Classic {
+    pub const ALL: [Classic; 4] = [
+        Classic::Dipole,
+        Classic::Quadrupole,
+        Classic::Line,
+        Classic::Capacitor,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Classic::Dipole => "Dipole",
+            Classic::Quadrupole => "Quadrupole",
+            Classic::Line => "Line of Charges",
+            Classic::Capacitor => "Capacitor",
+        }
+    }
+
+    pub fn particles(&self) -> Vec<Particle> {
+        let particle = |charge, x, y| Particle {
+            charge,
+            position: Vector2::new(x, y),
+        };
+        let row = |charge, y, count: usize| {
+            (0..count).map(move |i| particle(charge, 0.2 + 0.6 * i as f32 / (count - 1) as f32, y))
+        };
+
+        match self {
+            Classic::Dipole => vec![particle(2.0, 0.3, 0.5), particle(-2.0, 0.7, 0.5)],
+            Classic::Quadrupole => vec![
+                particle(1.0, 0.4, 0.4),
+                particle(-1.0, 0.6, 0.4),
+                particle(1.0, 0.6, 0.6),
+                particle(-1.0, 0.4, 0.6),
+            ],
+            Classic::Line => row(1.0, 0.5, 7).collect(),
+            Classic::Capacitor => row(1.0, 0.6, 9).chain(row(-1.0, 0.4, 9)).collect(),
+        }
+    }
+}
@@ -20,12 +20,18 @@ struct Uniform {
 
     e_solutions: u32,
     v_solutions: u32,
+    e_density: f32,
+
+    e_color: vec3f,
+    v_color: vec3f,
+    positive_color: vec3f,
+    negative_color: vec3f,
 
     particles: array<Particle>
 }
 
 struct Particle {
-    charge: i32,
+    charge: f32,
     position: vec2f
 }
 
@@ -42,27 +48,36 @@ fn frag(in: VertexOutput) -> @location(0) vec4f {
     var pos = ((in.uv - 0.5 + offset) / ctx.scale + 0.5) * window;
 
     var field = vec2(0.0);
+    var gradient = vec2(0.0);
     for (var i = u32(0); i < arrayLength(&ctx.particles); i++) {
         var particle = ctx.particles[i];
         var delta = pos - window * particle.position;
-        field += f32(particle.charge) * cLog(delta);
+        field += particle.charge * cLog(delta);
+        gradient += particle.charge * delta / dot(delta, delta);
     }
 
-    var e = cos(field.y * f32(ctx.e_solutions));
-    var e_value = line_thickness - abs(e / fwidth(e));
+    // Line width from the analytic gradient rather than fwidth, which would
+    // draw a line wherever Im f jumps across a branch cut. Both functions
+    // change by |E| per pixel, one along E and the other across it.
+    var width = (abs(gradient.x) + abs(gradient.y)) / ctx.scale;
+
+    var e_n = ctx.e_density;
+    var e = cos(field.y * e_n);
+    var e_value = line_thickness - abs(e / (e_n * abs(sin(field.y * e_n)) * width));
 
-    var v = cos(field.x * f32(ctx.v_solutions));
-    var v_value = line_thickness - abs(v / fwidth(v));
+    var v_n = f32(ctx.v_solutions);
+    var v = cos(field.x * v_n);
+    var v_value = line_thickness - abs(v / (v_n * abs(sin(field.x * v_n)) * width));
 
-    var e_color = max(e_value, 0.0) * vec3(0.031, 0.482, 0.737);
-    var v_color = max(v_value, 0.0) * vec3(0.031, 0.596, 0.490);
+    var e_color = max(e_value, 0.0) * ctx.e_color;
+    var v_color = max(v_value, 0.0) * ctx.v_color;
     var frag = vec4(max(e_color, v_color), 1.0);
 
     for (var i = u32(0); i < arrayLength(&ctx.particles); i++) {
         var particle = ctx.particles[i];
 
         var delta = pos - window * particle.position;
-        var color = mix(vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), f32(particle.charge < 0));
+        var color = select(ctx.positive_color, ctx.negative_color, particle.charge < 0.0);
 
         var t = -(length(delta) - particle_radius * scale) / (fade_radius * scale);
         frag = mix(frag, vec4(color, 1.0), smoothstep(0.0, 1.0, t));
@@ -0,0 +1,105 @@
+//! Vector version of the plot drawn by `shader.wgsl`. Field lines are traced
+//! from the charges and equipotentials are contours of the potential, with
+//! the charges drawn on top.
+
+use std::{fmt::Write, fs, path::Path};
+
+use anyhow::Result;
+use compute::export::nalgebra::{Vector2, Vector3};
+
+use crate::{
+    contour, field, trace,
+    types::Uniform,
+    view::{self, PARTICLE_RADIUS},
+};
+
+/// Grid spacing for the equipotential contours, in pixels.
+const CELL: f32 = 2.0;
+/// Approximates the width of the antialiased lines in the shader.
+const STROKE_WIDTH: f32 = 2.0;
+/// Same as `fade_radius` in `shader.wgsl`, the markers are drawn solid out to
+/// the middle of the fade.
+const FADE_RADIUS: f32 = 0.05;
+
+pub fn save(ctx: &Uniform, path: &Path) -> Result<()> {
+    fs::write(path, svg(ctx))?;
+    Ok(())
+}
+
+/// Draws the scene at the size of `ctx.window`, in pixels.
+pub fn svg(ctx: &Uniform) -> String {
+    let window = ctx.window.map(|x| x as f32);
+    let mut out = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        window.x, window.y
+    );
+    out.push_str(r#"<rect width="100%" height="100%" fill="black"/>"#);
+
+    let n = ctx.v_solutions as f32;
+    let equipotentials = contour::contour(window, CELL, |x| {
+        let pos = view::screen_to_world(ctx, x);
+        (field::sample(ctx, pos).potential * n).cos()
+    });
+    path(&mut out, &equipotentials, ctx.v_color);
+
+    let field_lines = trace::field_lines(ctx)
+        .into_iter()
+        .map(|line| {
+            let points = line.points.iter();
+            points.map(|x| view::world_to_screen(ctx, *x)).collect()
+        })
+        .collect::<Vec<_>>();
+    path(&mut out, &field_lines, ctx.e_color);
+
+    markers(&mut out, ctx);
+    out.push_str("</svg>");
+    out
+}
+
+/// Writes polylines given in screen space as a single path.
+fn path(out: &mut String, lines: &[Vec<Vector2<f32>>], color: Vector3<f32>) {
+    let mut d = String::new();
+    for line in lines.iter().filter(|x| x.len() > 1) {
+        for (i, point) in line.iter().enumerate() {
+            let command = if i == 0 { 'M' } else { 'L' };
+            write!(d, "{command}{:.1} {:.1}", point.x, point.y).unwrap();
+        }
+    }
+
+    if !d.is_empty() {
+        write!(
+            out,
+            r#"<path d="{d}" fill="none" stroke="{}" stroke-width="{STROKE_WIDTH}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            hex(color)
+        )
+        .unwrap();
+    }
+}
+
+fn markers(out: &mut String, ctx: &Uniform) {
+    let window = ctx.window.map(|x| x as f32);
+    let radius = (PARTICLE_RADIUS - FADE_RADIUS / 2.0) * window.min() * ctx.scale;
+    for particle in &ctx.particles {
+        let center = view::world_to_screen(ctx, particle.position);
+        let color = if particle.charge < 0.0 {
+            ctx.negative_color
+        } else {
+            ctx.positive_color
+        };
+        write!(
+            out,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{radius:.1}" fill="{}"/>"#,
+            center.x,
+            center.y,
+            hex(color)
+        )
+        .unwrap();
+    }
+}
+
+fn hex(color: Vector3<f32>) -> String {
+    let [r, g, b] = color
+        .map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8)
+        .into();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
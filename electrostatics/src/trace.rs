@@ -0,0 +1,204 @@
+//! Field lines traced as polylines by integrating along the field from each
+//! charge, for vector export. Start angles are chosen so the traced lines
+//! are the ones `shader.wgsl` draws.
+
+use std::f32::consts::PI;
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{
+    field,
+    types::{Particle, Uniform},
+};
+
+/// Step length as a fraction of the distance to the nearest charge, and its
+/// bounds in pixels.
+const STEP: f32 = 0.05;
+const MIN_STEP: f32 = 0.5;
+const MAX_STEP: f32 = 10.0;
+/// Lines end on a charge once this close to it, in pixels.
+const CAPTURE: f32 = 1.0;
+const MAX_STEPS: usize = 10_000;
+/// Lines further than this many window sizes from the center have escaped.
+const ESCAPE: f32 = 4.0;
+
+#[derive(Debug, Clone)]
+pub struct FieldLine {
+    /// In world space, running along the field away from positive charges.
+    pub points: Vec<Vector2<f32>>,
+    /// Charge the line ends on, or `None` if it escapes.
+    pub end: Option<usize>,
+}
+
+/// Traces `2 n |q|` lines from every charge, with `n` from
+/// `field::line_density`. Lines joining two charges are traced once, from
+/// the positive end.
+pub fn field_lines(ctx: &Uniform) -> Vec<FieldLine> {
+    let window = ctx.window.map(|x| x as f32);
+    // Traced in pixels, so steps and the field match the drawn image.
+    let particles = ctx
+        .particles
+        .iter()
+        .map(|x| Particle {
+            position: x.position.component_mul(&window),
+            ..x.clone()
+        })
+        .collect::<Vec<_>>();
+    let limit = ESCAPE * window.max();
+    let n = field::line_density(ctx);
+
+    let mut lines = Vec::new();
+    for (i, particle) in particles.iter().enumerate() {
+        if particle.charge == 0.0 {
+            continue;
+        }
+
+        // Close to the charge ψ = q θ + the others' contribution, and the
+        // drawn lines are where n ψ = π / 2 + k π.
+        let others = particles
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, x)| field::evaluate(std::slice::from_ref(x), particle.position, UNIT))
+            .map(|x| x.stream)
+            .sum::<f32>();
+
+        let count = (2.0 * n * particle.charge.abs()).round() as usize;
+        for k in 0..count {
+            let theta = ((k as f32 + 0.5) * PI / n - others) / particle.charge;
+            let mut line = trace(&particles, i, theta, limit);
+            if particle.charge < 0.0 {
+                if line.end.is_some() {
+                    continue;
+                }
+                line.points.reverse();
+            }
+
+            line.points
+                .iter_mut()
+                .for_each(|x| *x = x.component_div(&window));
+            lines.push(line);
+        }
+    }
+
+    lines
+}
+
+const UNIT: Vector2<f32> = Vector2::new(1.0, 1.0);
+
+/// Follows the field from charge `start` at angle `theta`, against it if the
+/// charge is negative, with fourth order Runge-Kutta on the unit field.
+fn trace(particles: &[Particle], start: usize, theta: f32, limit: f32) -> FieldLine {
+    let origin = particles[start].position;
+    let sign = particles[start].charge.signum();
+    let direction = |pos: Vector2<f32>| {
+        let field = field::evaluate(particles, pos, UNIT).field;
+        field.try_normalize(0.0).map(|x| x * sign)
+    };
+    let nearest = |pos: Vector2<f32>| {
+        (particles.iter().enumerate())
+            .filter(|(j, _)| *j != start)
+            .map(|(j, x)| (j, (x.position - pos).magnitude()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    };
+
+    let mut pos = origin + Vector2::new(theta.cos(), theta.sin()) * MIN_STEP;
+    let mut line = FieldLine {
+        points: vec![origin, pos],
+        end: None,
+    };
+
+    let center =
+        particles.iter().map(|x| x.position).sum::<Vector2<f32>>() / particles.len() as f32;
+    for _ in 0..MAX_STEPS {
+        let (closest, dist) = nearest(pos).unwrap_or((start, f32::INFINITY));
+        if dist < CAPTURE {
+            line.points.push(particles[closest].position);
+            line.end = Some(closest);
+            break;
+        }
+        if (pos - center).magnitude() > limit {
+            break;
+        }
+
+        let step = (dist.min((pos - origin).magnitude()) * STEP).clamp(MIN_STEP, MAX_STEP);
+        let rk4 = || {
+            let k1 = direction(pos)?;
+            let k2 = direction(pos + k1 * step / 2.0)?;
+            let k3 = direction(pos + k2 * step / 2.0)?;
+            let k4 = direction(pos + k3 * step)?;
+            Some((k1 + k2 * 2.0 + k3 * 2.0 + k4) / 6.0)
+        };
+
+        // Stagnation points have no direction to follow.
+        let Some(delta) = rk4() else {
+            break;
+        };
+        pos += delta * step;
+        line.points.push(pos);
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::field_lines;
+    use crate::types::{Particle, Uniform};
+
+    fn particle(charge: f32, x: f32, y: f32) -> Particle {
+        Particle {
+            charge,
+            position: Vector2::new(x, y),
+        }
+    }
+
+    #[test]
+    fn lines_join_opposite_charges() {
+        let scenes = [
+            vec![particle(1.0, 0.3, 0.5), particle(-1.0, 0.7, 0.5)],
+            vec![
+                particle(2.0, 0.5, 0.5),
+                particle(-1.0, 0.3, 0.45),
+                particle(-1.0, 0.7, 0.6),
+            ],
+            vec![
+                particle(1.5, 0.35, 0.3),
+                particle(-1.0, 0.6, 0.35),
+                particle(1.0, 0.7, 0.7),
+                particle(-1.5, 0.3, 0.65),
+            ],
+        ];
+
+        for particles in scenes {
+            let ctx = Uniform {
+                window: Vector2::new(1000, 800),
+                e_solutions: 4,
+                particles,
+                ..Default::default()
+            };
+            let lines = field_lines(&ctx);
+
+            for line in &lines {
+                let first = line.points[0];
+                let start = ctx.particles.iter().find(|x| x.position == first).unwrap();
+                let end = line.end.map(|x| &ctx.particles[x]);
+                let end = end.unwrap_or_else(|| panic!("line from {start:?} escaped"));
+
+                assert!(
+                    start.charge > 0.0 && end.charge < 0.0,
+                    "{start:?} to {end:?}"
+                );
+                assert_eq!(line.points.last(), Some(&end.position));
+            }
+
+            // Neutral scenes have every line leaving a positive charge end on
+            // a negative one, and none escape from the negative charges.
+            let positive = ctx.particles.iter().filter(|x| x.charge > 0.0);
+            let expected = positive.map(|x| 2.0 * 4.0 * x.charge).sum::<f32>();
+            assert_eq!(lines.len(), expected as usize);
+        }
+    }
+}
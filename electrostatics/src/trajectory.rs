@@ -0,0 +1,265 @@
+//! A test charge moving through the field of the fixed charges. Offsets are
+//! measured like `field::evaluate` with the shorter window side as one, so
+//! the body feels `a = q E / m` and keeps `m |v|² / 2 + q V` constant.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{
+    field,
+    types::{Particle, Uniform},
+    view,
+};
+
+/// Trail points kept, the oldest are dropped first.
+const MAX_TRAIL: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    /// Explicit Euler, first order and gains energy on orbits.
+    Euler,
+    /// Velocity Verlet, second order and symplectic.
+    Verlet,
+    /// Classic fourth order Runge-Kutta.
+    RungeKutta,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Body {
+    /// In world space.
+    pub position: Vector2<f32>,
+    /// In shorter window sides per unit time.
+    pub velocity: Vector2<f32>,
+    pub charge: f32,
+    pub mass: f32,
+}
+
+pub struct Trajectory {
+    /// Velocity, charge and mass given to launched bodies.
+    pub launch: Body,
+    pub integrator: Integrator,
+    pub dt: f32,
+    /// Steps taken each frame.
+    pub steps: u32,
+    /// Stop the body on reaching a charge's marker, instead of letting it
+    /// pass through the singularity.
+    pub collide: bool,
+
+    pub body: Option<Body>,
+    /// World positions of the body, oldest first.
+    pub trail: Vec<Vector2<f32>>,
+    /// Set when the body hits a charge or leaves the finite numbers.
+    pub stopped: bool,
+}
+
+impl Integrator {
+    pub const ALL: [Integrator; 3] = [
+        Integrator::Euler,
+        Integrator::Verlet,
+        Integrator::RungeKutta,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Integrator::Euler => "Euler",
+            Integrator::Verlet => "Velocity Verlet",
+            Integrator::RungeKutta => "Runge-Kutta 4",
+        }
+    }
+}
+
+impl Trajectory {
+    pub fn launch(&mut self, position: Vector2<f32>) {
+        self.body = Some(Body {
+            position,
+            ..self.launch
+        });
+        self.trail = vec![position];
+        self.stopped = false;
+    }
+
+    pub fn clear(&mut self) {
+        self.body = None;
+        self.trail.clear();
+    }
+
+    /// Takes this frame's steps through the charges in `ctx`.
+    pub fn advance(&mut self, ctx: &Uniform) {
+        let Some(body) = &mut self.body else {
+            return;
+        };
+        if self.stopped {
+            return;
+        }
+
+        let metric = metric(ctx);
+        for _ in 0..self.steps {
+            step(&ctx.particles, metric, body, self.integrator, self.dt);
+            let hit = self.collide && view::hit(ctx, body.position).is_some();
+            if hit || !body.position.iter().all(|x| x.is_finite()) {
+                self.stopped = true;
+                break;
+            }
+        }
+
+        if body.position.iter().all(|x| x.is_finite()) {
+            self.trail.push(body.position);
+        }
+        if self.trail.len() > MAX_TRAIL {
+            self.trail.drain(..self.trail.len() - MAX_TRAIL);
+        }
+    }
+}
+
+/// Scales world offsets so the shorter window side is one.
+pub fn metric(ctx: &Uniform) -> Vector2<f32> {
+    let window = ctx.window.map(|x| x as f32);
+    window / window.min()
+}
+
+/// Advances `body` by `dt` through the field of `particles`.
+pub fn step(
+    particles: &[Particle],
+    metric: Vector2<f32>,
+    body: &mut Body,
+    integrator: Integrator,
+    dt: f32,
+) {
+    let (charge, mass) = (body.charge, body.mass);
+    let acceleration =
+        |pos: Vector2<f32>| field::evaluate(particles, pos, metric).field * charge / mass;
+    // Velocities are in metric units, positions in world space.
+    let moved = |pos: Vector2<f32>, velocity: Vector2<f32>, dt: f32| {
+        pos + velocity.component_div(&metric) * dt
+    };
+
+    let (pos, vel) = (body.position, body.velocity);
+    match integrator {
+        Integrator::Euler => {
+            body.position = moved(pos, vel, dt);
+            body.velocity += acceleration(pos) * dt;
+        }
+        Integrator::Verlet => {
+            let half = vel + acceleration(pos) * dt / 2.0;
+            body.position = moved(pos, half, dt);
+            body.velocity = half + acceleration(body.position) * dt / 2.0;
+        }
+        Integrator::RungeKutta => {
+            let (k1x, k1v) = (vel, acceleration(pos));
+            let (k2x, k2v) = (
+                vel + k1v * dt / 2.0,
+                acceleration(moved(pos, k1x, dt / 2.0)),
+            );
+            let (k3x, k3v) = (
+                vel + k2v * dt / 2.0,
+                acceleration(moved(pos, k2x, dt / 2.0)),
+            );
+            let (k4x, k4v) = (vel + k3v * dt, acceleration(moved(pos, k3x, dt)));
+
+            body.position = moved(pos, (k1x + k2x * 2.0 + k3x * 2.0 + k4x) / 6.0, dt);
+            body.velocity += (k1v + k2v * 2.0 + k3v * 2.0 + k4v) / 6.0 * dt;
+        }
+    }
+}
+
+/// Kinetic plus potential energy of `body`.
+pub fn energy(particles: &[Particle], metric: Vector2<f32>, body: &Body) -> f32 {
+    let potential = field::evaluate(particles, body.position, metric).potential;
+    body.mass * body.velocity.norm_squared() / 2.0 + body.charge * potential
+}
+
+impl Default for Trajectory {
+    fn default() -> Self {
+        Self {
+            launch: Body {
+                position: Vector2::zeros(),
+                velocity: Vector2::zeros(),
+                charge: 1.0,
+                mass: 1.0,
+            },
+            integrator: Integrator::Verlet,
+            dt: 1e-3,
+            steps: 10,
+            collide: true,
+
+            body: None,
+            trail: Vec::new(),
+            stopped: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{energy, metric, step, Body, Integrator, Trajectory};
+    use crate::types::{Particle, Uniform};
+
+    fn ctx() -> Uniform {
+        Uniform {
+            window: Vector2::new(800, 600),
+            particles: vec![Particle {
+                charge: -1.0,
+                position: Vector2::new(0.5, 0.5),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn energy_conserved() {
+        let ctx = ctx();
+        let metric = metric(&ctx);
+
+        // Circular orbits around a line charge all have v² = |q Q| / m, a bit
+        // faster makes the orbit eccentric. 0.15 world units across is 0.2 in
+        // metric units.
+        let start = Body {
+            position: Vector2::new(0.65, 0.5),
+            velocity: Vector2::new(0.0, 1.2),
+            charge: 1.0,
+            mass: 1.0,
+        };
+        let initial = energy(&ctx.particles, metric, &start);
+
+        let mut drift = Vec::new();
+        for integrator in Integrator::ALL {
+            let mut body = start;
+            let mut max_error = 0f32;
+            for _ in 0..10_000 {
+                step(&ctx.particles, metric, &mut body, integrator, 1e-3);
+                let error = energy(&ctx.particles, metric, &body) - initial;
+                max_error = max_error.max(error.abs());
+            }
+            drift.push(max_error);
+        }
+
+        let [euler, verlet, runge_kutta] = drift[..] else {
+            unreachable!()
+        };
+        assert!(verlet < 1e-3 && runge_kutta < 1e-3, "{drift:?}");
+        assert!(euler > 10.0 * verlet, "{drift:?}");
+    }
+
+    #[test]
+    fn collision_stops_body() {
+        let ctx = ctx();
+        let mut trajectory = Trajectory {
+            steps: 100,
+            ..Default::default()
+        };
+
+        // Dropped from rest, the body falls straight onto the charge.
+        let start = Vector2::new(0.5, 0.8);
+        trajectory.launch(start);
+        for _ in 0..100 {
+            trajectory.advance(&ctx);
+        }
+
+        assert!(trajectory.stopped);
+        let body = trajectory.body.unwrap();
+        let offset = (body.position - Vector2::new(0.5, 0.5)).component_mul(&metric(&ctx));
+        assert!(offset.magnitude() < 0.1 && offset.y > 0.0, "{body:?}");
+        assert_eq!(trajectory.trail.first(), Some(&start));
+    }
+}
@@ -0,0 +1,58 @@
+use compute::export::nalgebra::{Vector2, Vector3};
+use encase::ShaderType;
+use serde::{Deserialize, Serialize};
+
+use crate::scene::Classic;
+
+/// Everything the shader draws. Saved as a scene, less the window size.
+#[derive(ShaderType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Uniform {
+    #[serde(skip)]
+    pub window: Vector2<u32>,
+    pub scale: f32,
+    pub position: Vector2<f32>,
+
+    pub e_solutions: u32,
+    pub v_solutions: u32,
+    /// Field lines per unit of `Im f`, set from `field::line_density` before
+    /// each upload.
+    #[serde(skip)]
+    pub e_density: f32,
+
+    /// Field and equipotential lines, then positive and negative charges.
+    pub e_color: Vector3<f32>,
+    pub v_color: Vector3<f32>,
+    pub positive_color: Vector3<f32>,
+    pub negative_color: Vector3<f32>,
+
+    #[size(runtime)]
+    pub particles: Vec<Particle>,
+}
+
+#[derive(ShaderType, Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub charge: f32,
+    pub position: Vector2<f32>,
+}
+
+impl Default for Uniform {
+    fn default() -> Self {
+        Self {
+            window: Vector2::zeros(),
+            scale: 1.0,
+            position: Vector2::zeros(),
+
+            e_solutions: 5,
+            v_solutions: 5,
+            e_density: 5.0,
+
+            e_color: Vector3::new(0.031, 0.482, 0.737),
+            v_color: Vector3::new(0.031, 0.596, 0.490),
+            positive_color: Vector3::new(1.0, 0.0, 0.0),
+            negative_color: Vector3::new(0.0, 0.0, 1.0),
+
+            particles: Classic::Dipole.particles(),
+        }
+    }
+}
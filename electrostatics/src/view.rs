@@ -0,0 +1,45 @@
+//! Mapping between window pixels and world space, where charge positions
+//! run from zero to one across the window. Mirrors the transform at the top
+//! of `frag` in `shader.wgsl`.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::types::Uniform;
+
+/// Drawn radius of a charge as a fraction of the shorter window side, same
+/// as `particle_radius` in `shader.wgsl`.
+pub const PARTICLE_RADIUS: f32 = 0.1;
+
+/// Maps a pixel position (origin top left) to world space.
+pub fn screen_to_world(ctx: &Uniform, screen: Vector2<f32>) -> Vector2<f32> {
+    let window = ctx.window.map(|x| x as f32);
+    let uv = Vector2::new(screen.x / window.x, 1.0 - screen.y / window.y);
+    let offset = ctx.position / window.min();
+    (uv - Vector2::repeat(0.5) + offset) / ctx.scale + Vector2::repeat(0.5)
+}
+
+/// Inverse of `screen_to_world`.
+pub fn world_to_screen(ctx: &Uniform, world: Vector2<f32>) -> Vector2<f32> {
+    let window = ctx.window.map(|x| x as f32);
+    let offset = ctx.position / window.min();
+    let uv = (world - Vector2::repeat(0.5)) * ctx.scale + Vector2::repeat(0.5) - offset;
+    Vector2::new(uv.x * window.x, (1.0 - uv.y) * window.y)
+}
+
+/// Distance between two world positions in units of the shorter window
+/// side, as the shader measures it.
+pub fn distance(ctx: &Uniform, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let window = ctx.window.map(|x| x as f32);
+    (a - b).component_mul(&window).magnitude() / window.min()
+}
+
+/// Index of the charge drawn under `pos`, the closest one if they overlap.
+pub fn hit(ctx: &Uniform, pos: Vector2<f32>) -> Option<usize> {
+    ctx.particles
+        .iter()
+        .map(|x| distance(ctx, x.position, pos))
+        .enumerate()
+        .filter(|(_, dist)| *dist < PARTICLE_RADIUS)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
@@ -0,0 +1,217 @@
+// Shared by every gravity shader, mirrors `types.rs` and `three_body.rs`.
+// Expects the including shader to bind `ctx: Uniform`.
+
+struct Uniform {
+    window: vec2f,
+    origin: vec2f,
+    dt: f32,
+    time: f32,
+
+    particles: u32,
+    radius: f32,
+
+    n_body: u32,
+    gravity: f32,
+
+    attractor_a: vec2f,
+    attractor_b: vec2f,
+    attractor_mass: vec2f,
+    frame: u32,
+
+    linear_drag: f32,
+    quadratic_drag: f32,
+    relaxation: f32,
+
+    coulomb: f32,
+    magnetic: f32,
+
+    softening: f32,
+    fault: u32,
+
+    style: u32,
+    glow: f32,
+    streak: f32,
+    hdr: u32,
+    exposure: f32,
+}
+
+struct Particle {
+    position: vec2f,
+    velocity: vec2f,
+    mass: f32,
+    species: u32,
+}
+
+struct Species {
+    color: vec3f,
+    response: vec2f,
+    boundary: u32,
+    charge: f32,
+}
+
+struct Charge {
+    position: vec2f,
+    charge: f32,
+}
+
+const FRAME_FIXED: u32 = 0;
+const FRAME_ORBITING: u32 = 1;
+const FRAME_ROTATING: u32 = 2;
+
+const STYLE_DISC: u32 = 0;
+const STYLE_GLOW: u32 = 1;
+const STYLE_STREAK: u32 = 2;
+const STYLE_PIXEL: u32 = 3;
+
+const FAULT_REMOVE: u32 = 0;
+const FAULT_RESET: u32 = 1;
+
+const BOUNDARY_OPEN: u32 = 0;
+const BOUNDARY_WRAP: u32 = 1;
+const BOUNDARY_REFLECT: u32 = 2;
+const BOUNDARY_ABSORB: u32 = 3;
+
+// Angular velocity of the two attractors on a circular orbit about each other.
+fn angular_velocity() -> f32 {
+    let d = distance(ctx.attractor_a, ctx.attractor_b);
+    return sqrt((ctx.attractor_mass.x + ctx.attractor_mass.y) / (d * d * d));
+}
+
+fn barycenter() -> vec2f {
+    let m = ctx.attractor_mass;
+    return (ctx.attractor_a * m.x + ctx.attractor_b * m.y) / (m.x + m.y);
+}
+
+fn attractor(i: u32) -> vec2f {
+    var pos = select(ctx.attractor_a, ctx.attractor_b, i == 1);
+    if ctx.frame == FRAME_ORBITING {
+        let center = barycenter();
+        pos = center + rotate(pos - center, angular_velocity() * ctx.time);
+    }
+    return pos;
+}
+
+// Gravitational acceleration towards the two attractors, each scaled by the
+// matching component of `response`.
+fn attractor_force(pos: vec2f, response: vec2f) -> vec2f {
+    return softened_pull(attractor(0u) - pos, response.x * ctx.attractor_mass.x)
+        + softened_pull(attractor(1u) - pos, response.y * ctx.attractor_mass.y);
+}
+
+fn attractor_potential(pos: vec2f) -> f32 {
+    return softened_potential(pos - attractor(0u), ctx.attractor_mass.x)
+        + softened_potential(pos - attractor(1u), ctx.attractor_mass.y);
+}
+
+// Plummer softened inverse square pull along `diff`, mirrors
+// `three_body::softened_pull`. Zero when `diff` and the softening both are.
+fn softened_pull(diff: vec2f, mass: f32) -> vec2f {
+    let r2 = dot(diff, diff) + ctx.softening * ctx.softening;
+    if r2 == 0.0 {
+        return vec2(0.0);
+    }
+    return diff * mass / (r2 * sqrt(r2));
+}
+
+fn softened_potential(diff: vec2f, mass: f32) -> f32 {
+    return -mass / sqrt(dot(diff, diff) + ctx.softening * ctx.softening);
+}
+
+// Centrifugal and Coriolis terms when simulating in the co-rotating frame.
+fn frame_acceleration(pos: vec2f, vel: vec2f) -> vec2f {
+    if ctx.frame != FRAME_ROTATING {
+        return vec2(0.0);
+    }
+
+    let omega = angular_velocity();
+    return omega * omega * (pos - barycenter()) + 2.0 * omega * vec2(vel.y, -vel.x);
+}
+
+fn rotate(v: vec2f, angle: f32) -> vec2f {
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec2(c * v.x - s * v.y, s * v.x + c * v.y);
+}
+
+// Mirrors `Boundary::apply`, absorbed particles are marked with zero mass.
+fn apply_boundary(particle: Particle, boundary: u32) -> Particle {
+    var out = particle;
+    switch boundary {
+        case BOUNDARY_WRAP: {
+            out.position = fract(out.position);
+        }
+        case BOUNDARY_REFLECT: {
+            for (var i = 0; i < 2; i++) {
+                if out.position[i] < 0.0 {
+                    out.position[i] = -out.position[i];
+                    out.velocity[i] = abs(out.velocity[i]);
+                } else if out.position[i] > 1.0 {
+                    out.position[i] = 2.0 - out.position[i];
+                    out.velocity[i] = -abs(out.velocity[i]);
+                }
+            }
+        }
+        case BOUNDARY_ABSORB: {
+            if any(out.position < vec2(0.0)) || any(out.position > vec2(1.0)) {
+                out.mass = 0.0;
+                out.velocity = vec2(0.0);
+            }
+        }
+        default: {}
+    }
+    return out;
+}
+
+// Drag, plus relaxation of the velocity towards the circular orbit about the
+// barycenter that `central` would support. Mirrors `dissipation.rs`.
+fn dissipation(pos: vec2f, vel: vec2f, central: vec2f) -> vec2f {
+    var acceleration = -ctx.linear_drag * vel - ctx.quadratic_drag * length(vel) * vel;
+
+    if ctx.relaxation != 0.0 {
+        let r = pos - barycenter();
+        let dist = length(r);
+        let inward = -dot(central, r) / dist;
+        if dist > 0.0 && inward > 0.0 {
+            let spin = select(1.0, -1.0, r.x * vel.y - r.y * vel.x < 0.0);
+            let tangent = vec2(-r.y, r.x) / dist * spin;
+            let circular = tangent * sqrt(inward * dist);
+            acceleration += ctx.relaxation * (circular - vel);
+        }
+    }
+
+    return acceleration;
+}
+
+// Glow needs additive blending, which is done by accumulating into a buffer.
+fn accumulate() -> bool {
+    return ctx.hdr != 0 || ctx.style == STYLE_GLOW;
+}
+
+// Length of a particle's streak in world units.
+fn streak_offset(vel: vec2f) -> vec2f {
+    return vel * ctx.streak;
+}
+
+fn is_finite(x: f32) -> bool {
+    return (bitcast<u32>(x) & 0x7f800000u) != 0x7f800000u;
+}
+
+fn particle_finite(p: Particle) -> bool {
+    return is_finite(p.position.x) && is_finite(p.position.y)
+        && is_finite(p.velocity.x) && is_finite(p.velocity.y)
+        && is_finite(p.mass);
+}
+
+// Replacement for a particle whose step went non-finite, given its state
+// before the step. Mirrors `guard::recover`.
+fn recover(prev: Particle) -> Particle {
+    if ctx.fault == FAULT_RESET && particle_finite(prev) {
+        return Particle(prev.position, vec2(0.0), prev.mass, prev.species);
+    }
+
+    var position = prev.position;
+    if !(is_finite(position.x) && is_finite(position.y)) {
+        position = vec2(0.0);
+    }
+    return Particle(position, vec2(0.0), 0.0, prev.species);
+}
@@ -1,35 +1,97 @@
-@group(0) @binding(0) var<storage, read_write> particles: array<Particle>;
-@group(0) @binding(1) var<uniform> ctx: Uniform;
+// `workgroup_size`, `common.wgsl` and `potential.wgsl` are prepended by the
+// host, see `shader::compute_shader`.
 
-struct Uniform {
-    window: vec2f,
-    dt: f32,
+// Particles are read from the front buffer and written into the back buffer,
+// so every invocation sees the state from the start of the step.
+@group(0) @binding(0) var<storage, read> particles: array<Particle>;
+@group(0) @binding(1) var<storage, read_write> next: array<Particle>;
+@group(0) @binding(2) var<uniform> ctx: Uniform;
+@group(0) @binding(3) var<storage, read> potentials: array<Potential>;
+@group(0) @binding(4) var<storage, read> species: array<Species>;
+@group(0) @binding(5) var<storage, read> charges: array<Charge>;
+// Number of particles found non-finite, see `recover`.
+@group(0) @binding(6) var<storage, read_write> faults: atomic<u32>;
 
-    particles: u32,
-    radius: f32,
-}
-
-struct Particle {
-    position: vec2f,
-    velocity: vec2f,
-    mass: f32
-}
+// Positions and masses of the particles currently loaded by the workgroup.
+var<workgroup> tile: array<vec3f, workgroup_size>;
 
 @compute
-@workgroup_size(1, 1, 1)
-fn main(@builtin(global_invocation_id) id: vec3<u32>) {
-    let a = particles[id.x];
+@workgroup_size(workgroup_size, 1, 1)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(local_invocation_id) local: vec3<u32>
+) {
+    let in_bounds = id.x < ctx.particles;
+    var a = Particle(vec2(0.0), vec2(0.0), 1.0, 0u);
+    if in_bounds {
+        a = particles[id.x];
+    }
+    let kind = species[min(a.species, arrayLength(&species) - 1)];
+
+    var central = attractor_force(a.position, kind.response);
+    for (var i = 0u; i < arrayLength(&potentials); i++) {
+        central += potential_force(potentials[i], a.position, ctx.time);
+    }
 
-    let object = vec2(0.4, 0.5);
-    let object2 = vec2(0.6, 0.5);
+    var force = (central
+                + frame_acceleration(a.position, a.velocity)
+                + dissipation(a.position, a.velocity, central)) * ctx.dt * a.mass
+                + kind.charge * ctx.coulomb * charge_field(a.position) * ctx.dt;
 
-    let diff = object - a.position;
-    let diff2 = object2 - a.position;
+    if ctx.n_body != 0 {
+        for (var start = 0u; start < ctx.particles; start += workgroup_size) {
+            let j = start + local.x;
+            tile[local.x] = vec3(0.0);
+            if j < ctx.particles {
+                tile[local.x] = vec3(particles[j].position, particles[j].mass);
+            }
+            workgroupBarrier();
 
-    let force = normalize(diff) * ctx.dt * (a.mass * 1.0) / dot(diff, diff)
-                + normalize(diff2) * ctx.dt * (a.mass * 1.0) / dot(diff2, diff2);
+            for (var k = 0u; k < workgroup_size; k++) {
+                let other = tile[k];
+                let diff = other.xy - a.position;
+                // A corrupted particle must not spread to the others.
+                let valid = other.z > 0.0 && is_finite(other.x) && is_finite(other.y);
+                if start + k != id.x && valid {
+                    force += softened_pull(diff, other.z) * ctx.dt * ctx.gravity * a.mass;
+                }
+            }
+            workgroupBarrier();
+        }
+    }
+
+    if !in_bounds {
+        return;
+    }
+
+    if a.mass <= 0.0 {
+        next[id.x] = a;
+        return;
+    }
 
     let acceleration = force / a.mass;
-    particles[id.x].velocity += acceleration;
-    particles[id.x].position += particles[id.x].velocity * ctx.dt;
+    a.velocity += acceleration;
+    if ctx.magnetic != 0.0 {
+        a.velocity = rotate(a.velocity, -kind.charge * ctx.magnetic * ctx.dt / a.mass);
+    }
+    a.position += a.velocity * ctx.dt;
+    var out = apply_boundary(a, kind.boundary);
+    if !particle_finite(out) {
+        atomicAdd(&faults, 1u);
+        out = recover(particles[id.x]);
+    }
+    next[id.x] = out;
+}
+
+// Two dimensional Coulomb field of the fixed charges, mirrors `charge.rs`.
+fn charge_field(pos: vec2f) -> vec2f {
+    var field = vec2(0.0);
+    for (var i = 0u; i < arrayLength(&charges); i++) {
+        let diff = pos - charges[i].position;
+        let dist = dot(diff, diff) + ctx.softening * ctx.softening;
+        if dist > 0.0 {
+            field += diff * charges[i].charge / dist;
+        }
+    }
+    return field;
 }
@@ -0,0 +1,163 @@
+// `common.wgsl` and `potential.wgsl` are prepended by the host,
+// see `shader::overlay_shader`. Mirrored on the CPU by `field.rs`.
+// Drawn as a full screen quad underneath the particles.
+
+@group(0) @binding(0) var<uniform> ctx: Uniform;
+@group(0) @binding(1) var<storage, read> overlay: Overlay;
+@group(0) @binding(2) var<storage, read> potentials: array<Potential>;
+
+struct Overlay {
+    lagrange: u32,
+    jacobi: f32,
+    points: array<vec2f, 5>,
+
+    field: u32,
+    range: vec2f,
+    contour_spacing: f32,
+    arrows: u32,
+    arrow_spacing: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(1) uv: vec2f,
+};
+
+const line_thickness: f32 = 1.5;
+const marker_radius: f32 = 4.0;
+const field_brightness: f32 = 0.5;
+
+@vertex
+fn vert(
+    @location(0) pos: vec4f,
+    @location(1) uv: vec2f,
+) -> VertexOutput {
+    return VertexOutput(pos, uv);
+}
+
+@fragment
+fn frag(in: VertexOutput) -> @location(0) vec4f {
+    let screen = in.pos.xy - ctx.origin;
+    let pos = screen_to_world(screen);
+    let pixel = max(ctx.window.x, ctx.window.y);
+
+    var color = vec3(0.0);
+
+    if overlay.field != 0 {
+        let phi = total_potential(pos);
+        let t = clamp((phi - overlay.range.x) / (overlay.range.y - overlay.range.x), 0.0, 1.0);
+        color = viridis(t) * field_brightness;
+
+        if overlay.contour_spacing > 0.0 {
+            let f = phi / overlay.contour_spacing;
+            let line = clamp(line_thickness - abs(fract(f + 0.5) - 0.5) / fwidth(f), 0.0, 1.0);
+            color = mix(color, vec3(0.8), line * 0.5);
+        }
+    }
+
+    if overlay.arrows != 0 {
+        color = max(color, vec3(arrow(screen) * 0.6));
+    }
+
+    if overlay.lagrange != 0 {
+        let phi = effective_potential(pos);
+
+        // Zero-velocity curves through the collinear points, plus the curve
+        // for the chosen Jacobi constant C = -2Φ.
+        for (var i = 0u; i < 3; i++) {
+            let line = contour(phi, effective_potential(overlay.points[i]));
+            color = max(color, line * vec3(0.3, 0.3, 0.3));
+        }
+        color = max(color, contour(phi, -overlay.jacobi / 2.0) * vec3(0.031, 0.596, 0.490));
+
+        for (var i = 0u; i < 5; i++) {
+            let dist = distance(pos, overlay.points[i]) * pixel;
+            let marker = clamp(marker_radius - dist, 0.0, 1.0);
+            color = mix(color, vec3(0.918, 0.224, 0.263), marker);
+        }
+    }
+
+    return vec4(color, 1.0);
+}
+
+// Inverse of the particle transform in `render.wgsl`.
+fn screen_to_world(screen: vec2f) -> vec2f {
+    let clip = (screen / ctx.window * 2.0 - 1.0) * vec2(1.0, -1.0);
+    let scale = ctx.window.yx / min(ctx.window.x, ctx.window.y);
+    return (clip / scale + 1.0) / 2.0;
+}
+
+// The potential and force felt by the particles, see `compute.wgsl`.
+fn total_potential(pos: vec2f) -> f32 {
+    var phi = attractor_potential(pos);
+    for (var i = 0u; i < arrayLength(&potentials); i++) {
+        phi += potential_value(potentials[i], pos, ctx.time);
+    }
+    return phi;
+}
+
+fn total_force(pos: vec2f) -> vec2f {
+    var force = attractor_force(pos, vec2(1.0));
+    for (var i = 0u; i < arrayLength(&potentials); i++) {
+        force += potential_force(potentials[i], pos, ctx.time);
+    }
+    return force;
+}
+
+// Coverage of the field-direction arrow in the grid cell containing `screen`.
+fn arrow(screen: vec2f) -> f32 {
+    let spacing = overlay.arrow_spacing;
+    let center = (floor(screen / spacing) + 0.5) * spacing;
+
+    let force = total_force(screen_to_world(center));
+    if dot(force, force) == 0.0 {
+        return 0.0;
+    }
+
+    // Screen space has y pointing down.
+    let dir = normalize(vec2(force.x, -force.y));
+    let side = vec2(-dir.y, dir.x);
+    let half = 0.4 * spacing;
+    let tip = center + dir * half;
+    let head = 0.3 * half;
+
+    let dist = min(
+        segment(screen, center - dir * half, tip),
+        min(
+            segment(screen, tip, tip - (dir + side) * head),
+            segment(screen, tip, tip - (dir - side) * head)
+        )
+    );
+    return clamp(1.0 - dist, 0.0, 1.0);
+}
+
+fn segment(p: vec2f, a: vec2f, b: vec2f) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
+fn contour(value: f32, level: f32) -> f32 {
+    let delta = value - level;
+    return clamp(line_thickness - abs(delta / fwidth(delta)), 0.0, 1.0);
+}
+
+// Effective potential of the two attractors in the frame rotating with them.
+fn effective_potential(pos: vec2f) -> f32 {
+    let omega = angular_velocity();
+    let r = pos - barycenter();
+    return attractor_potential(pos) - 0.5 * omega * omega * dot(r, r);
+}
+
+// Polynomial fit of matplotlib's viridis.
+fn viridis(t: f32) -> vec3f {
+    let c0 = vec3(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
+    let c1 = vec3(0.1050930431085774, 1.404613529898575, 1.384590162594685);
+    let c2 = vec3(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
+    let c3 = vec3(-4.634230498983486, -5.799100973351585, -19.33244095627987);
+    let c4 = vec3(6.228269936347081, 14.17993336680509, 56.69055260068105);
+    let c5 = vec3(4.776384997670288, -13.74514537774601, -65.35303263337234);
+    let c6 = vec3(-5.435455855934631, 4.645852612178535, 26.3124352495832);
+    return c0 + t * (c1 + t * (c2 + t * (c3 + t * (c4 + t * (c5 + t * c6)))));
+}
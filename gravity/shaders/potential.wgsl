@@ -0,0 +1,85 @@
+// Background potentials, mirrors `potential.rs`.
+// Forces are per unit mass, i.e. -∇Φ.
+
+struct Potential {
+    kind: u32,
+    center: vec2f,
+    params: vec4f,
+}
+
+const POTENTIAL_UNIFORM: u32 = 0;
+const POTENTIAL_HARMONIC: u32 = 1;
+const POTENTIAL_LOGARITHMIC_HALO: u32 = 2;
+const POTENTIAL_ROTATING_BAR: u32 = 3;
+const POTENTIAL_MIYAMOTO_NAGAI: u32 = 4;
+
+fn potential_value(potential: Potential, pos: vec2f, time: f32) -> f32 {
+    let p = potential.params;
+    let delta = pos - potential.center;
+
+    switch potential.kind {
+        case POTENTIAL_UNIFORM: {
+            return -dot(p.xy, pos);
+        }
+        case POTENTIAL_HARMONIC: {
+            return 0.5 * p.x * p.x * dot(delta, delta);
+        }
+        case POTENTIAL_LOGARITHMIC_HALO: {
+            return 0.5 * p.x * p.x * log(p.y * p.y + dot(delta, delta));
+        }
+        case POTENTIAL_ROTATING_BAR: {
+            let bar = bar_frame(delta, p.z * time);
+            let b2 = p.y * p.y;
+            return -p.x * (bar.x * bar.x - bar.y * bar.y) / (b2 + dot(bar, bar));
+        }
+        case POTENTIAL_MIYAMOTO_NAGAI: {
+            let ab = p.y + p.z;
+            return -p.x / sqrt(dot(delta, delta) + ab * ab);
+        }
+        default: {
+            return 0.0;
+        }
+    }
+}
+
+fn potential_force(potential: Potential, pos: vec2f, time: f32) -> vec2f {
+    let p = potential.params;
+    let delta = pos - potential.center;
+
+    switch potential.kind {
+        case POTENTIAL_UNIFORM: {
+            return p.xy;
+        }
+        case POTENTIAL_HARMONIC: {
+            return -p.x * p.x * delta;
+        }
+        case POTENTIAL_LOGARITHMIC_HALO: {
+            return -p.x * p.x * delta / (p.y * p.y + dot(delta, delta));
+        }
+        case POTENTIAL_ROTATING_BAR: {
+            let angle = p.z * time;
+            let bar = bar_frame(delta, angle);
+            let b2 = p.y * p.y;
+            let denom = pow(b2 + dot(bar, bar), 2.0);
+            let force = vec2(
+                2.0 * p.x * bar.x * (b2 + 2.0 * bar.y * bar.y),
+                -2.0 * p.x * bar.y * (b2 + 2.0 * bar.x * bar.x)
+            ) / denom;
+            return bar_frame(force, -angle);
+        }
+        case POTENTIAL_MIYAMOTO_NAGAI: {
+            let ab = p.y + p.z;
+            return -p.x * delta / pow(dot(delta, delta) + ab * ab, 1.5);
+        }
+        default: {
+            return vec2(0.0);
+        }
+    }
+}
+
+// Rotates `v` by `-angle`, taking it into a frame rotated by `angle`.
+fn bar_frame(v: vec2f, angle: f32) -> vec2f {
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec2(c * v.x + s * v.y, c * v.y - s * v.x);
+}
@@ -1,23 +1,17 @@
+// `common.wgsl` is prepended by the host, see `shader::render_shader`.
+// Only used when `accumulate()` is false, otherwise see `splat.wgsl`.
+
 @group(0) @binding(0) var<storage, read_write> particles: array<Particle>;
 @group(0) @binding(1) var<uniform> ctx: Uniform;
-
-struct Uniform {
-    window: vec2f,
-    dt: f32,
-
-    particles: u32,
-    radius: f32,
-}
-
-struct Particle {
-    position: vec2f,
-    velocity: vec2f,
-    mass: f32
-}
+@group(0) @binding(2) var<storage, read> species: array<Species>;
 
 struct VertexOutput {
     @builtin(position) pos: vec4<f32>,
-    @location(1) uv: vec2<f32>
+    // Position within the sprite, in units of the particle radius.
+    @location(1) local: vec2<f32>,
+    @location(2) color: vec3<f32>,
+    // Half length of the streak's core segment, in units of the radius.
+    @location(3) half: f32,
 };
 
 @vertex
@@ -27,20 +21,53 @@ fn vert(
     @location(1) uv: vec2<f32>,
 ) -> VertexOutput {
     let particle = particles[index];
+    let color = species[min(particle.species, arrayLength(&species) - 1)].color;
+
+    // Absorbed particles are moved outside of the clip volume.
+    if particle.mass <= 0.0 || accumulate() {
+        return VertexOutput(vec4(0.0, 0.0, 2.0, 1.0), pos.xy, color, 0.0);
+    }
 
     let scale = ctx.window.yx / min(ctx.window.x, ctx.window.y);
-    let position = scale * ((pos.xy * ctx.radius) + (particle.position * 2.0 - 1.0));
+    let center = particle.position * 2.0 - 1.0;
+
+    if ctx.style == STYLE_PIXEL {
+        let position = scale * center + pos.xy / ctx.window;
+        return VertexOutput(vec4(position, 1.0, 1.0), vec2(0.0), color, 0.0);
+    }
+
+    // Discs are streaks of zero length. The quad is stretched along the
+    // velocity and shifted so the particle sits at the head of its trail.
+    var half = 0.0;
+    var dir = vec2(1.0, 0.0);
+    if ctx.style == STYLE_STREAK {
+        let offset = streak_offset(particle.velocity) * 2.0;
+        let length = length(offset);
+        if length > 0.0 {
+            dir = offset / length;
+            half = length / 2.0 / ctx.radius;
+        }
+    }
+
+    let side = vec2(-dir.y, dir.x);
+    let local = vec2(pos.x * (half + 1.0), pos.y);
+    let world = center + (dir * (local.x - half) + side * local.y) * ctx.radius;
 
-    return VertexOutput(vec4(position, 1.0, 1.0), uv);
+    return VertexOutput(vec4(scale * world, 1.0, 1.0), local, color, half);
 }
 
 @fragment
 fn frag(in: VertexOutput) -> @location(0) vec4<f32> {
-    let dist = 0.5 - length(in.uv - vec2(0.5));
-    let border = 0.001 / ctx.radius;
+    if ctx.style == STYLE_PIXEL {
+        return vec4(in.color, 1.0);
+    }
 
-    let inside = step(border, dist);
-    let edge = step(0.0, dist) * (1.0 - inside) * (dist / border);
+    let dist = length(vec2(max(abs(in.local.x) - in.half, 0.0), in.local.y));
+    let coverage = clamp((1.0 - dist) / fwidth(dist), 0.0, 1.0);
 
-    return vec4(inside + edge);
+    // Keep the quad's corners transparent over the overlay and other sprites.
+    if coverage == 0.0 {
+        discard;
+    }
+    return vec4(in.color * coverage, coverage);
 }
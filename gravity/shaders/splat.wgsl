@@ -0,0 +1,100 @@
+// `workgroup_size` and `common.wgsl` are prepended by the host, see
+// `shader::splat_shader`. Adds every particle's sprite into `accum`, which
+// `tonemap.wgsl` then resolves and clears.
+
+@group(0) @binding(0) var<storage, read> particles: array<Particle>;
+@group(0) @binding(1) var<uniform> ctx: Uniform;
+@group(0) @binding(2) var<storage, read> species: array<Species>;
+@group(0) @binding(3) var<storage, read_write> accum: array<atomic<u32>>;
+
+// Fixed point scale of the accumulated color, mirrored by `tonemap.wgsl`.
+const FIXED: f32 = 1024.0;
+// Largest distance in pixels a sprite may reach from its particle.
+const MAX_EXTENT: f32 = 64.0;
+
+@compute
+@workgroup_size(workgroup_size, 1, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= ctx.particles {
+        return;
+    }
+
+    let particle = particles[id.x];
+    if particle.mass <= 0.0 {
+        return;
+    }
+    let color = species[min(particle.species, arrayLength(&species) - 1)].color;
+
+    let pixels = max(ctx.window.x, ctx.window.y) / 2.0;
+    let center = world_to_screen(particle.position);
+    let radius = max(ctx.radius * pixels, 0.5);
+
+    if ctx.style == STYLE_PIXEL {
+        add(vec2i(floor(center)), color);
+        return;
+    }
+
+    var tail = center;
+    var extent = radius + 1.0;
+    if ctx.style == STYLE_GLOW {
+        extent = 3.0 * ctx.glow * radius;
+    } else if ctx.style == STYLE_STREAK {
+        let trail = world_to_screen(particle.position - streak_offset(particle.velocity)) - center;
+        tail = center + trail * min(1.0, 2.0 * MAX_EXTENT / max(length(trail), 1.0));
+    }
+
+    let lo = vec2i(floor(min(center, tail) - min(extent, MAX_EXTENT)));
+    let hi = vec2i(ceil(max(center, tail) + min(extent, MAX_EXTENT)));
+    let size = vec2i(ctx.window);
+    let start = max(lo, vec2i(0));
+    let end = min(hi, size - 1);
+
+    for (var y = start.y; y <= end.y; y++) {
+        for (var x = start.x; x <= end.x; x++) {
+            let pixel = vec2f(f32(x), f32(y)) + 0.5;
+            let dist = segment_distance(pixel, tail, center);
+
+            var weight = 0.0;
+            if ctx.style == STYLE_GLOW {
+                let sigma = ctx.glow * radius;
+                weight = exp(-dist * dist / (2.0 * sigma * sigma));
+            } else {
+                weight = clamp(radius - dist + 0.5, 0.0, 1.0);
+            }
+
+            if weight > 0.0 {
+                add(vec2i(x, y), color * weight);
+            }
+        }
+    }
+}
+
+fn add(pixel: vec2i, color: vec3f) {
+    let size = vec2i(ctx.window);
+    if any(pixel < vec2i(0)) || any(pixel >= size) {
+        return;
+    }
+
+    let index = 3u * u32(pixel.y * size.x + pixel.x);
+    for (var i = 0u; i < 3u; i++) {
+        atomicAdd(&accum[index + i], u32(color[i] * FIXED));
+    }
+}
+
+// Forward transform from `render.wgsl`, to pixels with the origin top left.
+fn world_to_screen(pos: vec2f) -> vec2f {
+    let scale = ctx.window.yx / min(ctx.window.x, ctx.window.y);
+    let clip = scale * (pos * 2.0 - 1.0);
+    return vec2(clip.x + 1.0, 1.0 - clip.y) * ctx.window / 2.0;
+}
+
+fn segment_distance(p: vec2f, a: vec2f, b: vec2f) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let len = dot(ba, ba);
+    if len == 0.0 {
+        return length(pa);
+    }
+    let h = clamp(dot(pa, ba) / len, 0.0, 1.0);
+    return length(pa - ba * h);
+}
@@ -0,0 +1,47 @@
+// `common.wgsl` is prepended by the host, see `shader::tonemap_shader`.
+// Drawn as a full screen quad over the particles when `accumulate()` is true,
+// resolving and clearing the buffer filled by `splat.wgsl`.
+
+@group(0) @binding(0) var<uniform> ctx: Uniform;
+@group(0) @binding(1) var<storage, read_write> accum: array<atomic<u32>>;
+
+const FIXED: f32 = 1024.0;
+
+struct VertexOutput {
+    @builtin(position) pos: vec4f,
+    @location(1) uv: vec2f,
+};
+
+@vertex
+fn vert(
+    @location(0) pos: vec4f,
+    @location(1) uv: vec2f,
+) -> VertexOutput {
+    return VertexOutput(pos, uv);
+}
+
+@fragment
+fn frag(in: VertexOutput) -> @location(0) vec4f {
+    let pixel = vec2u(in.pos.xy - ctx.origin);
+    let index = 3u * (pixel.y * u32(ctx.window.x) + pixel.x);
+    if index + 2u >= arrayLength(&accum) {
+        discard;
+    }
+
+    var color = vec3(0.0);
+    for (var i = 0u; i < 3u; i++) {
+        color[i] = f32(atomicExchange(&accum[index + i], 0u)) / FIXED;
+    }
+
+    // Leave the overlay visible where there are no particles.
+    if all(color == vec3(0.0)) {
+        discard;
+    }
+
+    color *= ctx.exposure;
+    if ctx.hdr != 0 {
+        // Reinhard, keeps dense cores from saturating to flat white.
+        color = color / (1.0 + color);
+    }
+    return vec4(min(color, vec3(1.0)), 1.0);
+}
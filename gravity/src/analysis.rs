@@ -0,0 +1,312 @@
+//! Statistics of the particle state, written to CSV by `export.rs`. Radii
+//! and velocity components are taken about `center`, usually the attractors'
+//! barycenter. Absorbed particles are ignored except by `escape_fraction`.
+
+use std::f32::consts::PI;
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{
+    phase::Quantity,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadialBin {
+    /// Midpoint of the annulus.
+    pub radius: f32,
+    pub count: u32,
+    /// Mass per unit area of the annulus.
+    pub density: f32,
+    pub mean_vr: f32,
+    pub mean_vt: f32,
+    /// Standard deviation of the radial and tangential velocity.
+    pub sigma_r: f32,
+    pub sigma_t: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramBin {
+    pub center: f32,
+    pub count: u32,
+}
+
+/// Splits `0..max_radius` into `bins` equal annuli and reports the surface
+/// density and velocity dispersion in each.
+pub fn radial_profile(
+    center: Vector2<f32>,
+    particles: &[Particle],
+    bins: usize,
+    max_radius: f32,
+) -> Vec<RadialBin> {
+    #[derive(Default, Clone, Copy)]
+    struct Sums {
+        count: u32,
+        mass: f32,
+        vr: [f32; 2],
+        vt: [f32; 2],
+    }
+
+    let width = max_radius / bins as f32;
+    let mut sums = vec![Sums::default(); bins];
+    for particle in particles.iter().filter(|x| x.mass > 0.0) {
+        let (r, vr, vt) = polar(center, particle);
+        let bin = (r / width) as usize;
+        if bin >= bins {
+            continue;
+        }
+
+        let sums = &mut sums[bin];
+        sums.count += 1;
+        sums.mass += particle.mass;
+        sums.vr[0] += vr;
+        sums.vr[1] += vr * vr;
+        sums.vt[0] += vt;
+        sums.vt[1] += vt * vt;
+    }
+
+    sums.iter()
+        .enumerate()
+        .map(|(i, sums)| {
+            let inner = i as f32 * width;
+            let outer = inner + width;
+            let n = sums.count.max(1) as f32;
+            let mean = |[sum, _]: [f32; 2]| sum / n;
+            let sigma =
+                |[sum, squares]: [f32; 2]| (squares / n - (sum / n).powi(2)).max(0.0).sqrt();
+
+            RadialBin {
+                radius: inner + width / 2.0,
+                count: sums.count,
+                density: sums.mass / (PI * (outer * outer - inner * inner)),
+                mean_vr: mean(sums.vr),
+                mean_vt: mean(sums.vt),
+                sigma_r: sigma(sums.vr),
+                sigma_t: sigma(sums.vt),
+            }
+        })
+        .collect()
+}
+
+/// Specific angular momentum `r × v` of every live particle.
+pub fn angular_momenta(center: Vector2<f32>, particles: &[Particle]) -> Vec<f32> {
+    particles
+        .iter()
+        .filter(|x| x.mass > 0.0)
+        .map(|x| (x.position - center).perp(&x.velocity))
+        .collect()
+}
+
+/// Counts `values` into `bins` equal bins spanning `range`, values outside it
+/// are dropped.
+pub fn histogram(values: &[f32], bins: usize, range: [f32; 2]) -> Vec<HistogramBin> {
+    let [min, max] = range;
+    let width = (max - min) / bins as f32;
+
+    let mut counts = vec![0; bins];
+    for &value in values {
+        let bin = ((value - min) / width).floor();
+        if bin >= 0.0 && (bin as usize) < bins {
+            counts[bin as usize] += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| HistogramBin {
+            center: min + (i as f32 + 0.5) * width,
+            count,
+        })
+        .collect()
+}
+
+/// Smallest range containing every value, widened slightly so the largest
+/// value falls inside the last bin of a `histogram`.
+pub fn range(values: &[f32]) -> [f32; 2] {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if min > max {
+        return [0.0, 1.0];
+    }
+    let pad = ((max - min) * 1e-3).max(1e-6);
+    [min, max + pad]
+}
+
+/// Fraction of particles that are unbound, i.e. have positive energy per
+/// `Quantity::Energy`, or were absorbed by their species' boundary.
+pub fn escape_fraction(ctx: &Uniform, world: &World, particles: &[Particle]) -> f32 {
+    if particles.is_empty() {
+        return 0.0;
+    }
+
+    let escaped = particles
+        .iter()
+        .filter(|x| x.mass <= 0.0 || Quantity::Energy.eval(ctx, world, x) > 0.0)
+        .count();
+    escaped as f32 / particles.len() as f32
+}
+
+fn polar(center: Vector2<f32>, particle: &Particle) -> (f32, f32, f32) {
+    let delta = particle.position - center;
+    let r = delta.magnitude();
+    let radial = delta.try_normalize(0.0).unwrap_or_else(Vector2::x);
+    let tangent = Vector2::new(-radial.y, radial.x);
+    (
+        r,
+        particle.velocity.dot(&radial),
+        particle.velocity.dot(&tangent),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use compute::export::nalgebra::Vector2;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{angular_momenta, escape_fraction, histogram, radial_profile};
+    use crate::{
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    const CENTER: Vector2<f32> = Vector2::new(0.5, 0.5);
+
+    fn particle(position: Vector2<f32>, velocity: Vector2<f32>) -> Particle {
+        Particle {
+            position,
+            velocity,
+            mass: 1.0,
+            species: 0,
+        }
+    }
+
+    /// Uniform disc of the given radius, rotating rigidly at `omega`.
+    fn disc(count: usize, radius: f32, omega: f32) -> Vec<Particle> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..count)
+            .map(|_| {
+                let r = radius * rng.gen::<f32>().sqrt();
+                let (sin, cos) = (rng.gen::<f32>() * TAU).sin_cos();
+                let offset = Vector2::new(cos, sin) * r;
+                particle(CENTER + offset, Vector2::new(-offset.y, offset.x) * omega)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn uniform_disc_has_flat_density() {
+        let (count, radius) = (200_000, 0.4);
+        let profile = radial_profile(CENTER, &disc(count, radius, 1.0), 10, radius);
+
+        let expected = count as f32 / (PI * radius * radius);
+        for bin in &profile {
+            assert!(
+                (bin.density / expected - 1.0).abs() < 0.05,
+                "{bin:?} != {expected}"
+            );
+        }
+        let total = profile.iter().map(|x| x.count).sum::<u32>();
+        assert_eq!(total as usize, count);
+    }
+
+    #[test]
+    fn rigid_rotation_is_cold() {
+        let omega = 2.0;
+        for bin in radial_profile(CENTER, &disc(10_000, 0.4, omega), 8, 0.4) {
+            assert!(bin.mean_vr.abs() < 1e-4, "{bin:?}");
+            // Rigid rotation still spreads v_t across the width of the bin.
+            let spread = omega * 0.4 / 8.0;
+            assert!(
+                (bin.mean_vt - omega * bin.radius).abs() < spread / 2.0,
+                "{bin:?}"
+            );
+            assert!(bin.sigma_r < 1e-4, "{bin:?}");
+            assert!(bin.sigma_t < spread, "{bin:?}");
+        }
+    }
+
+    #[test]
+    fn isotropic_dispersion() {
+        let sigma = 0.3;
+        let mut rng = StdRng::seed_from_u64(1);
+        let particles = (0..50_000)
+            .map(|i| {
+                let angle = i as f32 / 50_000.0 * TAU;
+                let position = CENTER + Vector2::new(angle.cos(), angle.sin()) * 0.2;
+                // Uniform on [-a, a] has standard deviation a / √3.
+                let a = sigma * 3f32.sqrt();
+                let velocity = Vector2::new(rng.gen_range(-a..a), rng.gen_range(-a..a));
+                particle(position, velocity)
+            })
+            .collect::<Vec<_>>();
+
+        let profile = radial_profile(CENTER, &particles, 1, 0.4);
+        assert!(
+            (profile[0].sigma_r - sigma).abs() < 0.01,
+            "{:?}",
+            profile[0]
+        );
+        assert!(
+            (profile[0].sigma_t - sigma).abs() < 0.01,
+            "{:?}",
+            profile[0]
+        );
+    }
+
+    #[test]
+    fn ring_angular_momentum() {
+        let (radius, speed) = (0.2, 1.75);
+        let particles = (0..100)
+            .map(|i| {
+                let (sin, cos) = (i as f32 / 100.0 * TAU).sin_cos();
+                particle(
+                    CENTER + Vector2::new(cos, sin) * radius,
+                    Vector2::new(-sin, cos) * speed,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let momenta = angular_momenta(CENTER, &particles);
+        for l in &momenta {
+            assert!((l - radius * speed).abs() < 1e-5);
+        }
+
+        let bins = histogram(&momenta, 4, [0.0, 0.4]);
+        assert_eq!(
+            bins.iter().map(|x| x.count).collect::<Vec<_>>(),
+            [0, 0, 0, 100]
+        );
+        assert!((bins[3].center - 0.35).abs() < 1e-6);
+    }
+
+    #[test]
+    fn escape_fraction_counts_unbound() {
+        let ctx = Uniform {
+            attractor_mass: Vector2::new(1.0, 0.0),
+            ..Default::default()
+        };
+        let a = ctx.attractor_a;
+
+        // Escape speed at distance r from a unit mass is sqrt(2 / r).
+        let r: f32 = 0.1;
+        let escape = (2.0 / r).sqrt();
+        let mut particles = Vec::new();
+        for speed in [0.5, 0.9, 1.1, 2.0] {
+            particles.push(particle(
+                a + Vector2::new(0.0, r),
+                Vector2::new(escape * speed, 0.0),
+            ));
+        }
+        particles.push(Particle {
+            mass: 0.0,
+            ..particles[0]
+        });
+
+        let fraction = escape_fraction(&ctx, &World::default(), &particles);
+        assert!((fraction - 3.0 / 5.0).abs() < 1e-6, "{fraction}");
+    }
+}
@@ -1,28 +1,65 @@
 use core::ops::RangeInclusive;
-use std::time::Instant;
+use std::{path::Path, time::Instant};
 
+use anyhow::Result;
 use compute::{
-    buffer::{StorageBuffer, UniformBuffer},
     export::{
-        egui::{emath::Numeric, Context, Slider, Ui, Window},
-        nalgebra::{Vector2, Vector3},
+        egui::{
+            emath::Numeric, CollapsingHeader, ComboBox, Context, DragValue, Slider, TextEdit, Ui,
+            Window,
+        },
+        nalgebra::Vector2,
         wgpu::RenderPass,
     },
     interactive::{GraphicsCtx, Interactive},
-    misc::mutability::Mutable,
-    pipeline::{compute::ComputePipeline, render::RenderPipeline},
 };
+use rand::{thread_rng, Rng};
 
-use crate::{types::Particle, Uniform};
+use crate::{
+    charge::PointCharge,
+    export::Exporter,
+    field,
+    guard::Fault,
+    harness::{self, Divergence},
+    pane::{self, Pane, MAX_PANES},
+    plots::Plots,
+    potential::Potential,
+    session::Recorder,
+    snapshot::Snapshot,
+    species::{Boundary, Species},
+    sprites::{self, Style},
+    three_body::{self, Frame},
+    types::{Overlay, Uniform},
+    world::World,
+};
 
 pub struct App {
-    pub render: RenderPipeline,
-    pub compute: ComputePipeline,
+    /// Created up front, only the first `pane_count` are stepped and drawn.
+    pub panes: Vec<Pane>,
+    pub pane_count: usize,
+    /// Pane the parameter editors act on.
+    pub selected: usize,
+    /// Copy every parameter edit to all panes.
+    pub sync: bool,
+    /// Every pane spawns the same particles from this seed.
+    pub seed: u64,
+    /// Divergence of each other pane from the first, while enabled.
+    pub divergence: Option<Vec<Divergence>>,
 
-    pub ctx: Uniform,
-    pub uniform: UniformBuffer<Uniform>,
-    pub dots: StorageBuffer<Vec<Particle>, Mutable>,
+    pub world: World,
+    pub snapshot_path: String,
+    /// Set while a session is being recorded, which follows the first pane.
+    pub recorder: Option<Recorder>,
+    pub session_path: String,
+    pub overlay_ctx: Overlay,
+    /// Plots and analysis export follow the first pane.
+    pub plots: Plots,
+    pub exporter: Exporter,
+    /// Frames between particle downloads for the plots and divergence, as
+    /// each one stalls the GPU.
+    pub sample_interval: u32,
 
+    pub frame: u64,
     pub last_frame: Instant,
 }
 
@@ -37,37 +74,698 @@ impl Interactive for App {
 
                 ui.separator();
 
-                dragger(ui, "Radius", &mut self.ctx.radius, 0.0..=0.1);
+                self.pane_editor(ui);
+
+                ui.separator();
+
+                let pane = &mut self.panes[self.selected];
+                style_editor(ui, &mut pane.ctx);
+
+                ui.separator();
+
+                let mut n_body = pane.ctx.n_body != 0;
+                ui.checkbox(&mut n_body, "N-Body");
+                pane.ctx.n_body = n_body as u32;
+                if n_body {
+                    dragger(ui, "Gravity", &mut pane.ctx.gravity, 0.0..=0.001);
+                }
 
                 ui.separator();
 
-                let mut dot_count = self.ctx.particles;
+                dissipation_editor(ui, &mut pane.ctx);
+                stability_editor(ui, pane);
+                attractor_editor(ui, &mut pane.ctx, &mut self.overlay_ctx);
+                field_editor(ui, &mut self.overlay_ctx);
+
+                if self.overlay_ctx.field != 0 {
+                    let pointer = ctx.input(|input| {
+                        let pos = input.pointer.hover_pos()?;
+                        Some(Vector2::new(pos.x, pos.y) * input.pixels_per_point)
+                    });
+                    let panes = &self.panes[..self.pane_count];
+                    if let Some((pane, pointer)) =
+                        pointer.and_then(|x| Some((panes.iter().find(|p| p.contains(x))?, x)))
+                    {
+                        let local = pointer - pane.ctx.origin;
+                        let pos = field::screen_to_world(pane.ctx.window, local);
+                        let sample = field::sample(&pane.ctx, &self.world.potentials, pos);
+                        ui.label(format!(
+                            "Φ: {:.3}, |F|: {:.3}",
+                            sample.potential,
+                            sample.force.magnitude()
+                        ));
+                    }
+                }
+
+                ui.separator();
+
+                let pane = &mut self.panes[self.selected];
+                let mut changed = potential_editor(ui, &mut self.world.potentials);
+                let mut respawn = false;
+                changed |= species_editor(ui, &mut self.world.species, &mut respawn);
+                changed |= charge_editor(ui, &mut pane.ctx, &mut self.world.charges);
+                if changed {
+                    for pane in &self.panes {
+                        pane.simulation.upload_world(&self.world).unwrap();
+                    }
+                }
+
+                ui.separator();
+
+                let pane = &mut self.panes[self.selected];
+                let mut dot_count = pane.ctx.particles;
                 dragger(ui, "Dots", &mut dot_count, 0..=65_535);
 
-                if dot_count != self.ctx.particles {
-                    self.ctx.particles = dot_count;
-                    let dots = (0..dot_count)
-                        .map(|_| Particle::random())
-                        .collect::<Vec<_>>();
-                    self.dots.upload(&dots).unwrap();
+                if dot_count != pane.ctx.particles || respawn {
+                    pane.ctx.particles = dot_count;
+                    self.reseed();
+                }
+
+                if self.sync {
+                    let ctx = self.panes[self.selected].ctx.clone();
+                    for pane in &mut self.panes {
+                        pane.sync(&ctx);
+                    }
                 }
+
+                ui.separator();
+
+                self.snapshot_editor(ui);
+                self.session_editor(ui);
+
+                ui.separator();
+
+                ui.checkbox(&mut self.plots.open, "Phase Space");
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut self.sample_interval).range(1..=600));
+                    ui.label("Frames per Sample");
+                });
+                export_editor(ui, &mut self.exporter);
             });
+
+        self.plots.ui(ctx);
     }
 
     fn render(&mut self, gcx: GraphicsCtx, render_pass: &mut RenderPass) {
         let screen = gcx.window.inner_size();
-        self.ctx.window = Vector2::new(screen.width as f32, screen.height as f32);
+        let window = Vector2::new(screen.width as f32, screen.height as f32);
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.observe(&self.panes[0].ctx, &self.world);
+        }
+
+        for (i, pane) in self.panes[..self.pane_count].iter_mut().enumerate() {
+            let [origin, size] = pane::viewport(window, i, self.pane_count);
+            pane.ctx.origin = origin;
+            pane.ctx.window = size;
 
-        self.uniform.upload(&self.ctx).unwrap();
-        self.compute
-            .dispatch(Vector3::new(self.ctx.particles, 1, 1));
-        self.render.draw_quad(render_pass, 0..self.ctx.particles);
+            render_pass.set_viewport(origin.x, origin.y, size.x, size.y, 0.0, 1.0);
+            render_pass.set_scissor_rect(
+                origin.x as u32,
+                origin.y as u32,
+                size.x as u32,
+                size.y as u32,
+            );
+            pane.step(&mut self.overlay_ctx, render_pass);
+            pane.draw(render_pass);
+        }
+        render_pass.set_viewport(0.0, 0.0, window.x, window.y, 0.0, 1.0);
+        render_pass.set_scissor_rect(0, 0, screen.width, screen.height);
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.step();
+        }
+
+        // Particles are only downloaded on frames something needs them.
+        self.frame += 1;
+        let sample = self
+            .frame
+            .is_multiple_of(self.sample_interval.max(1) as u64);
+        let export = self.exporter.tick();
+        let plots = self.plots.open && sample;
+        let divergence = self.divergence.is_some() && self.pane_count > 1 && sample;
+        if plots || export || divergence {
+            let ctx = &self.panes[0].ctx;
+            let particles = match self.panes[0].particles() {
+                Ok(particles) => particles,
+                Err(err) => {
+                    eprintln!("Failed to download particles: {err}");
+                    return;
+                }
+            };
+
+            if divergence {
+                let panes = &self.panes[1..self.pane_count];
+                let divergence = panes
+                    .iter()
+                    .map(|x| Ok(harness::divergence(&particles, &x.particles()?)))
+                    .collect::<Result<Vec<_>>>();
+                match divergence {
+                    Ok(divergence) => self.divergence = Some(divergence),
+                    Err(err) => eprintln!("Failed to download particles: {err}"),
+                }
+            }
+            if export {
+                if let Err(err) = self.exporter.export(ctx, &self.world, &particles) {
+                    eprintln!("Failed to export analysis: {err}");
+                    self.exporter.enabled = false;
+                }
+            }
+            if plots {
+                self.plots.record(ctx, &self.world, particles);
+            }
+        }
     }
 }
 
+impl App {
+    fn pane_editor(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Panes").show(ui, |ui| {
+            let mut count = self.pane_count;
+            dragger(ui, "Panes", &mut count, 1..=MAX_PANES);
+
+            ui.checkbox(&mut self.sync, "Sync Parameters");
+            ComboBox::from_label("Edit Pane")
+                .selected_text(format!("Pane {}", self.selected + 1))
+                .show_ui(ui, |ui| {
+                    for i in 0..count {
+                        ui.selectable_value(&mut self.selected, i, format!("Pane {}", i + 1));
+                    }
+                });
+
+            let mut seed = self.seed;
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut seed));
+                ui.label("Seed");
+            });
+
+            let mut divergence = self.divergence.is_some();
+            ui.checkbox(&mut divergence, "Divergence");
+            match (divergence, &self.divergence) {
+                (false, _) => self.divergence = None,
+                (true, None) => self.divergence = Some(Vec::new()),
+                (true, Some(divergence)) => {
+                    for (i, x) in divergence.iter().enumerate() {
+                        ui.label(format!(
+                            "Pane {}: position {:.2e}, velocity {:.2e}",
+                            i + 2,
+                            x.position,
+                            x.velocity
+                        ));
+                    }
+                }
+            }
+
+            // New panes start from the edited pane's parameters, and every
+            // pane restarts so they stay in step.
+            if count != self.pane_count {
+                let ctx = self.panes[self.selected].ctx.clone();
+                for pane in &mut self.panes[self.pane_count.min(count)..] {
+                    pane.sync(&ctx);
+                }
+                self.pane_count = count;
+                self.selected = self.selected.min(count - 1);
+                self.respawn();
+            } else if seed != self.seed {
+                self.seed = seed;
+                self.respawn();
+            }
+        });
+    }
+
+    /// Respawns every pane from a new seed.
+    fn reseed(&mut self) {
+        self.seed = thread_rng().gen();
+        self.respawn();
+    }
+
+    /// Spawns the same particles into every pane, using the edited pane's
+    /// particle count.
+    fn respawn(&mut self) {
+        let count = self.panes[self.selected].ctx.particles;
+        for pane in &mut self.panes {
+            pane.ctx.particles = count;
+        }
+
+        let dots = match &mut self.recorder {
+            Some(recorder) => recorder.spawn(&self.panes[0].ctx, &self.world),
+            None => harness::seeded_particles(self.seed, count, &self.world.species),
+        };
+        for pane in &self.panes {
+            pane.simulation.upload(&dots).unwrap();
+        }
+        self.plots.reset();
+        self.exporter.reset();
+    }
+
+    fn snapshot_editor(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Snapshot").show(ui, |ui| {
+            ui.add(TextEdit::singleline(&mut self.snapshot_path).hint_text("Path"));
+            ui.horizontal(|ui| {
+                let path = Path::new(&self.snapshot_path);
+                if ui.button("Save").clicked() {
+                    let pane = &self.panes[self.selected];
+                    let snapshot = Snapshot {
+                        ctx: pane.ctx.clone(),
+                        world: self.world.clone(),
+                        particles: pane.particles().unwrap(),
+                    };
+                    if let Err(err) = snapshot.save(path) {
+                        eprintln!("Failed to save snapshot: {err}");
+                    }
+                }
+
+                if ui.button("Load").clicked() {
+                    match Snapshot::load(path) {
+                        Ok(snapshot) => self.load(snapshot),
+                        Err(err) => eprintln!("Failed to load snapshot: {err}"),
+                    }
+                }
+            });
+        });
+    }
+
+    /// Recording starts from freshly spawned particles so the whole session
+    /// follows from its seed.
+    fn session_editor(&mut self, ui: &mut Ui) {
+        CollapsingHeader::new("Session").show(ui, |ui| {
+            ui.add(TextEdit::singleline(&mut self.session_path).hint_text("Path"));
+            ui.horizontal(|ui| match &self.recorder {
+                None => {
+                    if ui.button("Record").clicked() {
+                        let seed = thread_rng().gen();
+                        let ctx = &self.panes[0].ctx;
+                        self.recorder = Some(Recorder::new(seed, ctx, &self.world));
+                        self.respawn();
+                    }
+                }
+                Some(recorder) => {
+                    ui.label(format!(
+                        "Step {}, {} events",
+                        recorder.session().steps,
+                        recorder.session().events.len()
+                    ));
+                    if ui.button("Stop & Save").clicked() {
+                        let path = Path::new(&self.session_path);
+                        if let Err(err) = recorder.session().save(path) {
+                            eprintln!("Failed to save session: {err}");
+                        }
+                        self.recorder = None;
+                    }
+                }
+            });
+        });
+    }
+
+    /// Loads the snapshot into every pane, so they start from the same state.
+    fn load(&mut self, snapshot: Snapshot) {
+        let ctx = Uniform {
+            particles: snapshot.particles.len() as u32,
+            ..snapshot.ctx
+        };
+        self.world = snapshot.world;
+        if self.world.species.is_empty() {
+            self.world.species.push(Species::default());
+        }
+
+        for pane in &mut self.panes {
+            pane.sync(&ctx);
+            pane.simulation.upload_world(&self.world).unwrap();
+            pane.simulation.upload(&snapshot.particles).unwrap();
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.replace(&self.panes[0].ctx, &self.world, &snapshot.particles);
+        }
+
+        self.plots.reset();
+        self.exporter.reset();
+    }
+}
+
+fn stability_editor(ui: &mut Ui, pane: &mut Pane) {
+    CollapsingHeader::new("Stability").show(ui, |ui| {
+        dragger(ui, "Softening", &mut pane.ctx.softening, 0.0..=0.1);
+
+        let mut fault = Fault::from_u32(pane.ctx.fault);
+        ComboBox::from_label("Non-finite")
+            .selected_text(fault.name())
+            .show_ui(ui, |ui| {
+                for option in Fault::ALL {
+                    ui.selectable_value(&mut fault, option, option.name());
+                }
+            });
+        pane.ctx.fault = fault as u32;
+
+        // Only read back while shown, as the download waits on the GPU.
+        ui.horizontal(|ui| {
+            match pane.faults() {
+                Ok(faults) => ui.label(format!("Non-finite: {faults}")),
+                Err(err) => ui.label(format!("Non-finite: {err}")),
+            };
+            if ui.button("Clear").clicked() {
+                if let Err(err) = pane.clear_faults() {
+                    eprintln!("Failed to clear faults: {err}");
+                }
+            }
+        });
+    });
+}
+
 fn dragger<T: Numeric>(ui: &mut Ui, label: &str, value: &mut T, range: RangeInclusive<T>) {
     ui.horizontal(|ui| {
         ui.add(Slider::new(value, range));
         ui.label(label);
     });
 }
+
+fn style_editor(ui: &mut Ui, ctx: &mut Uniform) {
+    let mut style = Style::from_u32(ctx.style);
+    ComboBox::from_label("Style")
+        .selected_text(style.name())
+        .show_ui(ui, |ui| {
+            for option in Style::ALL {
+                ui.selectable_value(&mut style, option, option.name());
+            }
+        });
+    ctx.style = style as u32;
+
+    if style != Style::Pixel {
+        dragger(ui, "Radius", &mut ctx.radius, 0.0..=0.1);
+    }
+    match style {
+        Style::Glow => dragger(ui, "Glow Width", &mut ctx.glow, 0.1..=10.0),
+        Style::Streak => dragger(ui, "Streak Length", &mut ctx.streak, 0.0..=0.1),
+        _ => {}
+    }
+
+    let mut hdr = ctx.hdr != 0;
+    ui.checkbox(&mut hdr, "HDR");
+    ctx.hdr = hdr as u32;
+    if sprites::accumulate(ctx) {
+        dragger(ui, "Exposure", &mut ctx.exposure, 0.01..=10.0);
+    }
+}
+
+fn export_editor(ui: &mut Ui, exporter: &mut Exporter) {
+    CollapsingHeader::new("Analysis Export").show(ui, |ui| {
+        ui.checkbox(&mut exporter.enabled, "Write CSV");
+        ui.add(TextEdit::singleline(&mut exporter.directory).hint_text("Directory"));
+        if let Some(run) = exporter.run() {
+            ui.label(format!("Writing to {}", run.display()));
+        }
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut exporter.interval).range(1..=1_000_000));
+            ui.label("Step Interval");
+        });
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut exporter.radial_bins).range(1..=1000));
+            ui.label("Radial Bins");
+        });
+        dragger(ui, "Max Radius", &mut exporter.max_radius, 0.01..=1.0);
+        ui.horizontal(|ui| {
+            ui.add(DragValue::new(&mut exporter.momentum_bins).range(1..=1000));
+            ui.label("Angular Momentum Bins");
+        });
+    });
+}
+
+fn dissipation_editor(ui: &mut Ui, ctx: &mut Uniform) {
+    CollapsingHeader::new("Dissipation").show(ui, |ui| {
+        dragger(ui, "Linear Drag", &mut ctx.linear_drag, 0.0..=10.0);
+        dragger(ui, "Quadratic Drag", &mut ctx.quadratic_drag, 0.0..=10.0);
+        dragger(ui, "Relaxation", &mut ctx.relaxation, 0.0..=10.0);
+    });
+}
+
+fn attractor_editor(ui: &mut Ui, ctx: &mut Uniform, overlay: &mut Overlay) {
+    CollapsingHeader::new("Attractors").show(ui, |ui| {
+        let mut frame = Frame::from_u32(ctx.frame);
+        ComboBox::from_label("Frame")
+            .selected_text(frame.name())
+            .show_ui(ui, |ui| {
+                for option in Frame::ALL {
+                    ui.selectable_value(&mut frame, option, option.name());
+                }
+            });
+        ctx.frame = frame as u32;
+
+        for (i, label) in ["A", "B"].into_iter().enumerate() {
+            let pos = match i {
+                0 => &mut ctx.attractor_a,
+                _ => &mut ctx.attractor_b,
+            };
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut pos.x).speed(0.001));
+                ui.label("x");
+                ui.add(DragValue::new(&mut pos.y).speed(0.001));
+                ui.label(format!("Attractor {label}"));
+            });
+            dragger(
+                ui,
+                &format!("Mass {label}"),
+                &mut ctx.attractor_mass[i],
+                0.01..=2.0,
+            );
+        }
+
+        let mut lagrange = overlay.lagrange != 0;
+        ui.checkbox(&mut lagrange, "Lagrange Points");
+        overlay.lagrange = lagrange as u32;
+        if lagrange {
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut overlay.jacobi).speed(0.1));
+                ui.label("Jacobi Constant");
+                if ui.button("L1").clicked() {
+                    let l1 = three_body::lagrange_points(ctx)[0];
+                    overlay.jacobi = -2.0 * three_body::effective_potential(ctx, l1);
+                }
+            });
+        }
+    });
+}
+
+fn field_editor(ui: &mut Ui, overlay: &mut Overlay) {
+    CollapsingHeader::new("Field").show(ui, |ui| {
+        let mut field = overlay.field != 0;
+        ui.checkbox(&mut field, "Potential");
+        overlay.field = field as u32;
+        if field {
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(&mut overlay.range.x).speed(0.1));
+                ui.label("to");
+                ui.add(DragValue::new(&mut overlay.range.y).speed(0.1));
+                ui.label("Range");
+            });
+            dragger(
+                ui,
+                "Contour Spacing",
+                &mut overlay.contour_spacing,
+                0.0..=10.0,
+            );
+        }
+
+        let mut arrows = overlay.arrows != 0;
+        ui.checkbox(&mut arrows, "Field Arrows");
+        overlay.arrows = arrows as u32;
+        if arrows {
+            dragger(
+                ui,
+                "Arrow Spacing",
+                &mut overlay.arrow_spacing,
+                10.0..=200.0,
+            );
+        }
+    });
+}
+
+/// Returns true if any potential was added, removed or changed.
+fn potential_editor(ui: &mut Ui, potentials: &mut Vec<Potential>) -> bool {
+    let mut changed = false;
+
+    CollapsingHeader::new("Potentials").show(ui, |ui| {
+        let mut remove = None;
+        for (i, potential) in potentials.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(Potential::NAMES[potential.kind()]);
+                if ui.button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+
+            let mut param = |ui: &mut Ui, label: &str, value: &mut f32| {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(DragValue::new(value).speed(0.01)).changed();
+                    ui.label(label);
+                });
+            };
+
+            match potential {
+                Potential::Uniform(x) => {
+                    param(ui, "Field X", &mut x.field.x);
+                    param(ui, "Field Y", &mut x.field.y);
+                }
+                Potential::Harmonic(x) => {
+                    param(ui, "Center X", &mut x.center.x);
+                    param(ui, "Center Y", &mut x.center.y);
+                    param(ui, "ω", &mut x.omega);
+                }
+                Potential::LogarithmicHalo(x) => {
+                    param(ui, "Center X", &mut x.center.x);
+                    param(ui, "Center Y", &mut x.center.y);
+                    param(ui, "v₀", &mut x.v0);
+                    param(ui, "Core Radius", &mut x.core);
+                }
+                Potential::RotatingBar(x) => {
+                    param(ui, "Center X", &mut x.center.x);
+                    param(ui, "Center Y", &mut x.center.y);
+                    param(ui, "Strength", &mut x.strength);
+                    param(ui, "Scale", &mut x.scale);
+                    param(ui, "Pattern Speed", &mut x.pattern_speed);
+                }
+                Potential::MiyamotoNagai(x) => {
+                    param(ui, "Center X", &mut x.center.x);
+                    param(ui, "Center Y", &mut x.center.y);
+                    param(ui, "Mass", &mut x.mass);
+                    param(ui, "a", &mut x.a);
+                    param(ui, "b", &mut x.b);
+                }
+            }
+
+            ui.separator();
+        }
+
+        if let Some(i) = remove {
+            potentials.remove(i);
+            changed = true;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for (kind, name) in Potential::NAMES.iter().enumerate() {
+                if ui.button(format!("+ {name}")).clicked() {
+                    potentials.push(Potential::default_of(kind));
+                    changed = true;
+                }
+            }
+        });
+    });
+
+    changed
+}
+
+/// Returns true if any species was added, removed or changed. Sets `respawn`
+/// when the particles should be redrawn from the new species mix.
+fn species_editor(ui: &mut Ui, species: &mut Vec<Species>, respawn: &mut bool) -> bool {
+    let mut changed = false;
+
+    CollapsingHeader::new("Species").show(ui, |ui| {
+        let mut remove = None;
+        let removable = species.len() > 1;
+        for (i, species) in species.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut species.name);
+                changed |= ui.color_edit_button_rgb(&mut species.color).changed();
+                if removable && ui.button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    DragValue::new(&mut species.weight)
+                        .speed(0.01)
+                        .range(0.0..=f32::MAX),
+                );
+                ui.label("Weight");
+            });
+            ui.horizontal(|ui| {
+                let [min, max] = &mut species.mass;
+                ui.add(DragValue::new(min).speed(0.01).range(0.01..=f32::MAX));
+                ui.label("to");
+                ui.add(DragValue::new(max).speed(0.01).range(0.01..=f32::MAX));
+                ui.label("Mass");
+            });
+            ui.horizontal(|ui| {
+                let [a, b] = &mut species.response;
+                changed |= ui.add(DragValue::new(a).speed(0.01)).changed();
+                ui.label("A");
+                changed |= ui.add(DragValue::new(b).speed(0.01)).changed();
+                ui.label("B Response");
+            });
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(DragValue::new(&mut species.charge).speed(0.01))
+                    .changed();
+                ui.label("Charge");
+            });
+
+            let old = species.boundary;
+            ComboBox::from_id_salt(("boundary", i))
+                .selected_text(species.boundary.name())
+                .show_ui(ui, |ui| {
+                    for option in Boundary::ALL {
+                        ui.selectable_value(&mut species.boundary, option, option.name());
+                    }
+                });
+            changed |= species.boundary != old;
+
+            ui.separator();
+        }
+
+        if let Some(i) = remove {
+            species.remove(i);
+            changed = true;
+            *respawn = true;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("+ Species").clicked() {
+                species.push(Species {
+                    name: format!("Species {}", species.len() + 1),
+                    ..Default::default()
+                });
+                changed = true;
+            }
+            *respawn |= ui.button("Respawn").clicked();
+        });
+    });
+
+    changed
+}
+
+/// Returns true if any fixed charge was added, removed or changed.
+fn charge_editor(ui: &mut Ui, ctx: &mut Uniform, charges: &mut Vec<PointCharge>) -> bool {
+    let mut changed = false;
+
+    CollapsingHeader::new("Charges").show(ui, |ui| {
+        dragger(ui, "Coulomb Strength", &mut ctx.coulomb, 0.0..=10.0);
+        dragger(ui, "Magnetic Field", &mut ctx.magnetic, -100.0..=100.0);
+
+        let mut remove = None;
+        for (i, charge) in charges.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                for value in charge.position.iter_mut() {
+                    changed |= ui.add(DragValue::new(value).speed(0.001)).changed();
+                }
+                changed |= ui
+                    .add(DragValue::new(&mut charge.charge).speed(0.01))
+                    .changed();
+                if ui.button("Remove").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = remove {
+            charges.remove(i);
+            changed = true;
+        }
+
+        if ui.button("+ Charge").clicked() {
+            charges.push(PointCharge::default());
+            changed = true;
+        }
+    });
+
+    changed
+}
@@ -0,0 +1,50 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::shader::DEFAULT_WORKGROUP_SIZE;
+
+pub struct Args {
+    pub workgroup_size: u32,
+    pub cross_check: Option<u32>,
+    pub bench: bool,
+    pub replay: Option<PathBuf>,
+    pub output: PathBuf,
+    pub cpu: bool,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        let mut out = Self {
+            workgroup_size: DEFAULT_WORKGROUP_SIZE,
+            cross_check: None,
+            bench: false,
+            replay: None,
+            output: PathBuf::from("replay.json"),
+            cpu: false,
+        };
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing value for `{arg}`"))
+            };
+            match arg.as_str() {
+                "--workgroup-size" => out.workgroup_size = value()?.parse()?,
+                "--cross-check" => out.cross_check = Some(value()?.parse()?),
+                "--bench" => out.bench = true,
+                "--replay" => out.replay = Some(value()?.into()),
+                "--output" => out.output = value()?.into(),
+                "--cpu" => out.cpu = true,
+                _ => bail!("Unknown argument `{arg}`"),
+            }
+        }
+
+        if out.workgroup_size == 0 || out.workgroup_size > 256 {
+            bail!("Workgroup size must be between 1 and 256");
+        }
+
+        Ok(out)
+    }
+}
@@ -0,0 +1,51 @@
+//! Headless benchmark of the compute kernel, reporting steps per second.
+
+use std::time::Instant;
+
+use anyhow::Result;
+use compute::gpu::Gpu;
+
+use crate::{harness::seeded_particles, simulation::Simulation, types::Uniform, world::World};
+
+const PARTICLES: [u32; 3] = [1_024, 16_384, 65_535];
+const STEPS: u32 = 200;
+
+pub fn run(gpu: &Gpu, workgroup_size: u32) -> Result<()> {
+    println!("Workgroup size: {workgroup_size}");
+
+    for n_body in [0, 1] {
+        for particles in PARTICLES {
+            let ctx = Uniform {
+                particles,
+                n_body,
+                ..Default::default()
+            };
+
+            let world = World::default();
+            let uniform = gpu.create_uniform(&ctx)?;
+            let mut simulation = Simulation::new(
+                gpu,
+                &uniform,
+                seeded_particles(0, particles, &world.species),
+                &world,
+                workgroup_size,
+            )?;
+
+            simulation.step(particles);
+            simulation.front().download()?;
+
+            let start = Instant::now();
+            for _ in 0..STEPS {
+                simulation.step(particles);
+            }
+            // Reading the buffer back waits for every queued dispatch to finish.
+            simulation.front().download()?;
+
+            let rate = STEPS as f32 / start.elapsed().as_secs_f32();
+            let mode = if n_body != 0 { "n-body" } else { "attractors" };
+            println!("{mode:>10} {particles:>6} particles: {rate:>10.1} steps/s");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! Fixed point charges and a uniform magnetic field acting on charged
+//! species, mirrors the charge terms in `shaders/compute.wgsl`.
+//!
+//! Charges use the two dimensional Coulomb law, a field falling off as `1/r`
+//! with potential `-Q ln r`, the same field `electrostatics` draws.
+
+use compute::export::nalgebra::Vector2;
+use encase::ShaderType;
+use serde::{Deserialize, Serialize};
+
+#[derive(ShaderType, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PointCharge {
+    pub position: Vector2<f32>,
+    pub charge: f32,
+}
+
+/// Electric field of every charge at `pos`, like charges repel.
+pub fn field(charges: &[PointCharge], pos: Vector2<f32>, softening: f32) -> Vector2<f32> {
+    charges
+        .iter()
+        .map(|x| {
+            let diff = pos - x.position;
+            let dist = diff.norm_squared() + softening * softening;
+            if dist > 0.0 {
+                diff * x.charge / dist
+            } else {
+                Vector2::zeros()
+            }
+        })
+        .sum()
+}
+
+pub fn potential(charges: &[PointCharge], pos: Vector2<f32>, softening: f32) -> f32 {
+    let eps = softening * softening;
+    charges
+        .iter()
+        .map(|x| -x.charge * ((pos - x.position).norm_squared() + eps).ln() / 2.0)
+        .sum()
+}
+
+/// Angle the velocity turns through in one step of a uniform magnetic field
+/// `magnetic` along z, from the Lorentz force `q v × B`. Applied as an exact
+/// rotation so gyration does not gain energy.
+pub fn gyration(charge: f32, mass: f32, magnetic: f32, dt: f32) -> f32 {
+    -charge * magnetic * dt / mass
+}
+
+impl Default for PointCharge {
+    fn default() -> Self {
+        Self {
+            position: Vector2::repeat(0.5),
+            charge: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use compute::export::nalgebra::Vector2;
+
+    use super::{field, potential, PointCharge};
+    use crate::{
+        reference,
+        species::Species,
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    fn world(charge: f32, charges: Vec<PointCharge>) -> World {
+        World {
+            species: vec![Species {
+                charge,
+                response: [0.0, 0.0],
+                ..Default::default()
+            }],
+            charges,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cyclotron_radius() {
+        let (charge, mass, magnetic, speed) = (2.0, 0.5, 10.0, 1.5);
+        let ctx = Uniform {
+            particles: 1,
+            magnetic,
+            ..Default::default()
+        };
+        let world = world(charge, Vec::new());
+        let mut particle = [Particle {
+            position: Vector2::new(0.5, 0.5),
+            velocity: Vector2::new(speed, 0.0),
+            mass,
+            species: 0,
+        }];
+
+        // r = m v / (|q| B), over one period T = 2π m / (|q| B).
+        let radius = mass * speed / (charge * magnetic);
+        let period = TAU * mass / (charge * magnetic);
+
+        // Positive charge in +z field turns clockwise, so the center is below.
+        let center = Vector2::new(0.5, 0.5 - radius);
+        let steps = (period / ctx.dt).round() as usize;
+        let mut max_error = 0f32;
+        for _ in 0..steps {
+            reference::step(&ctx, &world, &mut particle);
+            let r = (particle[0].position - center).magnitude();
+            max_error = max_error.max((r - radius).abs());
+        }
+
+        assert!(max_error < 1e-2 * radius, "{max_error} vs {radius}");
+        assert!((particle[0].velocity.magnitude() - speed).abs() < 1e-4);
+        assert!((particle[0].position - Vector2::new(0.5, 0.5)).magnitude() < 1e-2 * radius);
+    }
+
+    #[test]
+    fn like_charges_repel() {
+        let ctx = Uniform {
+            particles: 1,
+            ..Default::default()
+        };
+        let source = PointCharge {
+            position: Vector2::new(0.3, 0.5),
+            charge: 1.0,
+        };
+
+        for (charge, sign) in [(1.0, 1.0), (-1.0, -1.0), (0.0, 0.0)] {
+            let mut particle = [Particle {
+                position: Vector2::new(0.5, 0.5),
+                velocity: Vector2::zeros(),
+                mass: 2.0,
+                species: 0,
+            }];
+            reference::step(&ctx, &world(charge, vec![source]), &mut particle);
+
+            // E = Q r / (r² + ε²) away from the charge, a = q E / m.
+            let r = 0.2f32;
+            let e = r / (r * r + ctx.softening.powi(2));
+            let expected = sign * ctx.coulomb * e / 2.0 * ctx.dt;
+            let velocity = particle[0].velocity;
+            assert!((velocity.x - expected).abs() < 1e-6, "{velocity:?}");
+            assert_eq!(velocity.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn field_is_negative_gradient() {
+        let charges = [
+            PointCharge {
+                position: Vector2::new(0.3, 0.4),
+                charge: 1.5,
+            },
+            PointCharge {
+                position: Vector2::new(0.7, 0.6),
+                charge: -0.5,
+            },
+        ];
+
+        let h = 1e-3;
+        for [x, y] in [[0.5, 0.5], [0.1, 0.8], [0.9, 0.2]] {
+            let pos = Vector2::new(x, y);
+            let phi = |d: Vector2<f32>| potential(&charges, pos + d, 0.01);
+            let gradient = Vector2::new(
+                phi(Vector2::new(h, 0.0)) - phi(Vector2::new(-h, 0.0)),
+                phi(Vector2::new(0.0, h)) - phi(Vector2::new(0.0, -h)),
+            ) / (2.0 * h);
+
+            let e = field(&charges, pos, 0.01);
+            assert!((e + gradient).magnitude() < 1e-2 * e.magnitude(), "{e:?}");
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Velocity-dependent damping that lets rings settle into discs, mirrors
+//! `dissipation` in `shaders/common.wgsl`.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{three_body, types::Uniform};
+
+/// Acceleration from linear and quadratic drag, plus relaxation of the
+/// velocity towards the circular orbit about the barycenter that the
+/// `central` acceleration would support. Relaxation keeps the particle's
+/// sense of rotation and does nothing where `central` points outwards.
+pub fn acceleration(
+    ctx: &Uniform,
+    pos: Vector2<f32>,
+    vel: Vector2<f32>,
+    central: Vector2<f32>,
+) -> Vector2<f32> {
+    let mut acceleration = -ctx.linear_drag * vel - ctx.quadratic_drag * vel.magnitude() * vel;
+
+    if ctx.relaxation != 0.0 {
+        let r = pos - three_body::barycenter(ctx);
+        let dist = r.magnitude();
+        let inward = -central.dot(&r) / dist;
+        if dist > 0.0 && inward > 0.0 {
+            let spin = if r.perp(&vel) < 0.0 { -1.0 } else { 1.0 };
+            let tangent = Vector2::new(-r.y, r.x) / dist * spin;
+            let circular = tangent * (inward * dist).sqrt();
+            acceleration += ctx.relaxation * (circular - vel);
+        }
+    }
+
+    acceleration
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use crate::{
+        reference,
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    #[test]
+    fn linear_drag_decays_exponentially() {
+        let ctx = Uniform {
+            particles: 1,
+            attractor_mass: Vector2::zeros(),
+            linear_drag: 5.0,
+            ..Default::default()
+        };
+        let v0 = Vector2::new(0.3, -0.4);
+        let mut particle = [Particle {
+            position: Vector2::new(0.5, 0.5),
+            velocity: v0,
+            mass: 1.0,
+            species: 0,
+        }];
+
+        let world = World::default();
+        let steps = 5000;
+        for _ in 0..steps {
+            reference::step(&ctx, &world, &mut particle);
+        }
+
+        let t = steps as f32 * ctx.dt;
+        let expected = v0.magnitude() * (-ctx.linear_drag * t).exp();
+        let speed = particle[0].velocity.magnitude();
+        assert!(
+            (speed / expected - 1.0).abs() < 1e-3,
+            "{speed} != {expected}"
+        );
+        assert!((particle[0].velocity.normalize() - v0.normalize()).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn relaxation_circularizes() {
+        let ctx = Uniform {
+            particles: 1,
+            attractor_mass: Vector2::new(1.0, 0.0),
+            relaxation: 50.0,
+            ..Default::default()
+        };
+        let mut particle = [Particle {
+            position: Vector2::new(0.4, 0.7),
+            velocity: Vector2::new(-3.0, 0.0),
+            mass: 1.0,
+            species: 0,
+        }];
+
+        let world = World::default();
+        for _ in 0..20_000 {
+            reference::step(&ctx, &world, &mut particle);
+        }
+
+        // Circular speed about a unit mass is sqrt(1 / r).
+        let r = (particle[0].position - ctx.attractor_a).magnitude();
+        let speed = particle[0].velocity.magnitude();
+        assert!(
+            (speed - (1.0 / r).sqrt()).abs() < 1e-2 * speed,
+            "{speed} at {r}"
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Periodically writes the statistics from `analysis.rs` to CSV files.
+
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+
+use crate::{
+    analysis, three_body,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+/// Each run, from the first export after the particles are replaced, writes
+/// into a fresh `run_<n>` subdirectory of `directory` so earlier runs are
+/// never overwritten. Each export writes `radial_<step>.csv` and
+/// `angular_momentum_<step>.csv` there and appends a row to `escape.csv`.
+pub struct Exporter {
+    pub enabled: bool,
+    pub directory: String,
+    /// Steps between exports.
+    pub interval: u32,
+    pub radial_bins: usize,
+    pub max_radius: f32,
+    pub momentum_bins: usize,
+
+    step: u64,
+    /// Directory of the current run, picked on its first export.
+    run: Option<PathBuf>,
+}
+
+impl Exporter {
+    /// Advances the step counter, returning true when the caller should
+    /// download the particles and call `export`.
+    pub fn tick(&mut self) -> bool {
+        self.step += 1;
+        self.enabled && self.step.is_multiple_of(self.interval.max(1) as u64)
+    }
+
+    /// Restarts the step count, for when the particles are replaced. The
+    /// next export starts a new run rather than touching the last one.
+    pub fn reset(&mut self) {
+        self.step = 0;
+        self.run = None;
+    }
+
+    pub fn run(&self) -> Option<&Path> {
+        self.run.as_deref()
+    }
+
+    pub fn export(&mut self, ctx: &Uniform, world: &World, particles: &[Particle]) -> Result<()> {
+        let run = match &self.run {
+            Some(run) if run.starts_with(&self.directory) => run.clone(),
+            _ => {
+                let run = self.next_run();
+                fs::create_dir_all(&run)?;
+                self.run = Some(run.clone());
+                run
+            }
+        };
+        let center = three_body::barycenter(ctx);
+
+        let mut radial = String::from("radius,count,density,mean_vr,mean_vt,sigma_r,sigma_t\n");
+        for bin in analysis::radial_profile(center, particles, self.radial_bins, self.max_radius) {
+            writeln!(
+                radial,
+                "{},{},{},{},{},{},{}",
+                bin.radius,
+                bin.count,
+                bin.density,
+                bin.mean_vr,
+                bin.mean_vt,
+                bin.sigma_r,
+                bin.sigma_t
+            )?;
+        }
+        fs::write(self.path(&run, "radial"), radial)?;
+
+        let momenta = analysis::angular_momenta(center, particles);
+        let range = analysis::range(&momenta);
+        let mut momentum = String::from("angular_momentum,count\n");
+        for bin in analysis::histogram(&momenta, self.momentum_bins, range) {
+            writeln!(momentum, "{},{}", bin.center, bin.count)?;
+        }
+        fs::write(self.path(&run, "angular_momentum"), momentum)?;
+
+        let escape = run.join("escape.csv");
+        let new = !escape.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(escape)?;
+        if new {
+            writeln!(file, "step,time,escape_fraction")?;
+        }
+        let fraction = analysis::escape_fraction(ctx, world, particles);
+        writeln!(file, "{},{},{}", self.step, ctx.time, fraction)?;
+
+        Ok(())
+    }
+
+    /// First `run_<n>` in `directory` that does not exist yet.
+    fn next_run(&self) -> PathBuf {
+        (0..)
+            .map(|i| Path::new(&self.directory).join(format!("run_{i:03}")))
+            .find(|x| !x.exists())
+            .unwrap()
+    }
+
+    fn path(&self, run: &Path, name: &str) -> PathBuf {
+        run.join(format!("{name}_{:08}.csv", self.step))
+    }
+}
+
+impl Default for Exporter {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: "analysis".into(),
+            interval: 1000,
+            radial_bins: 50,
+            max_radius: 0.5,
+            momentum_bins: 50,
+
+            step: 0,
+            run: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::Exporter;
+    use crate::{harness, types::Uniform, world::World};
+
+    #[test]
+    fn runs_kept_apart() {
+        let directory = env::temp_dir().join(format!("gravity-export-{}", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        let ctx = Uniform {
+            particles: 64,
+            ..Default::default()
+        };
+        let world = World::default();
+        let particles = harness::seeded_particles(0, ctx.particles, &world.species);
+        let mut exporter = Exporter {
+            enabled: true,
+            directory: directory.display().to_string(),
+            interval: 1,
+            ..Default::default()
+        };
+
+        for _ in 0..2 {
+            exporter.tick();
+            exporter.export(&ctx, &world, &particles).unwrap();
+        }
+        let first = exporter.run().unwrap().to_owned();
+
+        // Replacing the particles with export off must not touch the files.
+        exporter.enabled = false;
+        exporter.reset();
+        assert_eq!(fs::read_dir(&first).unwrap().count(), 5);
+
+        exporter.enabled = true;
+        exporter.tick();
+        exporter.export(&ctx, &world, &particles).unwrap();
+        let second = exporter.run().unwrap().to_owned();
+
+        assert_ne!(first, second);
+        assert_eq!(fs::read_dir(&first).unwrap().count(), 5);
+        assert_eq!(fs::read_dir(&second).unwrap().count(), 3);
+        let escape = fs::read_to_string(first.join("escape.csv")).unwrap();
+        assert_eq!(escape.lines().count(), 3);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
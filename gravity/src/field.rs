@@ -0,0 +1,103 @@
+//! CPU mirror of the field layer in `shaders/overlay.wgsl`.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{
+    potential::{Field, Potential},
+    three_body,
+    types::Uniform,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldSample {
+    pub potential: f32,
+    pub force: Vector2<f32>,
+}
+
+/// Potential and force per unit mass felt by a particle at `pos`, from the
+/// attractors and every background potential.
+pub fn sample(ctx: &Uniform, potentials: &[Potential], pos: Vector2<f32>) -> FieldSample {
+    FieldSample {
+        potential: three_body::potential(ctx, pos) + potentials.potential(pos, ctx.time),
+        force: three_body::attractor_force(ctx, pos) + potentials.force(pos, ctx.time),
+    }
+}
+
+/// Maps a pixel position (origin top left) to world space, the inverse of
+/// the particle transform in `render.wgsl`.
+pub fn screen_to_world(window: Vector2<f32>, screen: Vector2<f32>) -> Vector2<f32> {
+    let clip = (screen.component_div(&window) * 2.0 - Vector2::repeat(1.0))
+        .component_mul(&Vector2::new(1.0, -1.0));
+    let scale = window.yx() / window.x.min(window.y);
+    (clip.component_div(&scale) + Vector2::repeat(1.0)) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{sample, screen_to_world};
+    use crate::{
+        potential::{Potential, UniformField},
+        types::Uniform,
+    };
+
+    const POINTS: [[f32; 2]; 4] = [[0.5, 0.5], [0.3, 0.6], [0.8, 0.45], [0.1, 0.9]];
+
+    fn ctx() -> Uniform {
+        Uniform {
+            attractor_mass: Vector2::new(1.0, 0.5),
+            softening: 0.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn matches_analytic_potential() {
+        let ctx = ctx();
+        let g = Vector2::new(0.0, -2.0);
+        let potentials = [Potential::Uniform(UniformField { field: g })];
+
+        for [x, y] in POINTS {
+            let pos = Vector2::new(x, y);
+            let r1 = (pos - Vector2::new(0.4, 0.5)).magnitude();
+            let r2 = (pos - Vector2::new(0.6, 0.5)).magnitude();
+            let expected = -1.0 / r1 - 0.5 / r2 - g.dot(&pos);
+
+            let got = sample(&ctx, &potentials, pos).potential;
+            assert!((got - expected).abs() < 1e-5, "{got} != {expected}");
+        }
+    }
+
+    #[test]
+    fn force_is_negative_gradient() {
+        let ctx = ctx();
+        let h = 1e-3;
+        for [x, y] in POINTS {
+            let pos = Vector2::new(x, y);
+            let phi = |d: Vector2<f32>| sample(&ctx, &[], pos + d).potential;
+            let gradient = Vector2::new(
+                phi(Vector2::new(h, 0.0)) - phi(Vector2::new(-h, 0.0)),
+                phi(Vector2::new(0.0, h)) - phi(Vector2::new(0.0, -h)),
+            ) / (2.0 * h);
+
+            let force = sample(&ctx, &[], pos).force;
+            assert!((force + gradient).magnitude() < 1e-2 * force.magnitude().max(1.0));
+        }
+    }
+
+    #[test]
+    fn screen_inverts_render_transform() {
+        let window = Vector2::<f32>::new(1600.0, 900.0);
+        for [x, y] in POINTS {
+            let world = Vector2::new(x, y);
+
+            // Forward transform from `render.wgsl`, then clip to pixels.
+            let scale = window.yx() / window.x.min(window.y);
+            let clip = scale.component_mul(&(world * 2.0 - Vector2::repeat(1.0)));
+            let screen = Vector2::new(clip.x + 1.0, 1.0 - clip.y).component_mul(&window) / 2.0;
+
+            assert!((screen_to_world(window, screen) - world).magnitude() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Recovery of particles whose step produced a NaN or infinity, mirrors
+//! `recover` in `shaders/common.wgsl`.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::types::{Particle, Uniform};
+
+/// What to do with a particle that went non-finite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Mark it absorbed, like `Boundary::Absorb`.
+    Remove,
+    /// Put it back where it was before the step, at rest.
+    Reset,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 2] = [Fault::Remove, Fault::Reset];
+
+    pub fn from_u32(fault: u32) -> Self {
+        match fault {
+            1 => Fault::Reset,
+            _ => Fault::Remove,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Fault::Remove => "Remove",
+            Fault::Reset => "Reset",
+        }
+    }
+}
+
+pub fn is_finite(particle: &Particle) -> bool {
+    particle.position.iter().all(|x| x.is_finite())
+        && particle.velocity.iter().all(|x| x.is_finite())
+        && particle.mass.is_finite()
+}
+
+/// Replacement for a particle whose step went non-finite, given its state
+/// before the step. Falls back to removing it if that was not finite either.
+pub fn recover(ctx: &Uniform, prev: Particle) -> Particle {
+    if Fault::from_u32(ctx.fault) == Fault::Reset && is_finite(&prev) {
+        return Particle {
+            velocity: Vector2::zeros(),
+            ..prev
+        };
+    }
+
+    let mut position = prev.position;
+    if !position.iter().all(|x| x.is_finite()) {
+        position = Vector2::zeros();
+    }
+    Particle {
+        position,
+        velocity: Vector2::zeros(),
+        mass: 0.0,
+        species: prev.species,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{is_finite, Fault};
+    use crate::{
+        charge::PointCharge,
+        reference,
+        species::Species,
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    fn particle(position: Vector2<f32>, velocity: Vector2<f32>) -> Particle {
+        Particle {
+            position,
+            velocity,
+            mass: 1.0,
+            species: 0,
+        }
+    }
+
+    fn assert_finite(particles: &[Particle]) {
+        for particle in particles {
+            assert!(is_finite(particle), "{particle:?}");
+        }
+    }
+
+    #[test]
+    fn softening_keeps_attractor_finite() {
+        let ctx = Uniform {
+            particles: 1,
+            ..Default::default()
+        };
+        let mut particles = [particle(ctx.attractor_a, Vector2::zeros())];
+
+        assert_eq!(reference::step(&ctx, &World::default(), &mut particles), 0);
+        assert_finite(&particles);
+        assert_eq!(particles[0].mass, 1.0);
+    }
+
+    #[test]
+    fn unsoftened_singularities_are_recovered() {
+        let ctx = Uniform {
+            particles: 4,
+            n_body: 1,
+            softening: 0.0,
+            // Near the origin so a tiny offset is representable.
+            attractor_a: Vector2::zeros(),
+            ..Default::default()
+        };
+        let world = World {
+            species: vec![Species {
+                charge: 1.0,
+                ..Default::default()
+            }],
+            charges: vec![PointCharge {
+                position: Vector2::new(0.2, 0.2),
+                charge: 1.0,
+            }],
+            ..Default::default()
+        };
+
+        // Exactly on a singularity the pull is defined as zero, but just off
+        // an attractor it overflows.
+        let mut particles = [
+            particle(ctx.attractor_a + Vector2::new(1e-20, 0.0), Vector2::zeros()),
+            particle(Vector2::new(0.2, 0.2), Vector2::zeros()),
+            particle(Vector2::new(0.8, 0.8), Vector2::zeros()),
+            particle(Vector2::new(0.8, 0.8), Vector2::zeros()),
+        ];
+
+        assert_eq!(reference::step(&ctx, &world, &mut particles), 1);
+        for _ in 0..10 {
+            reference::step(&ctx, &world, &mut particles);
+            assert_finite(&particles);
+        }
+        assert_eq!(particles[0].mass, 0.0);
+        assert!(particles[1..].iter().all(|x| x.mass == 1.0));
+    }
+
+    #[test]
+    fn corrupted_input_is_recovered() {
+        let nan = f32::NAN;
+        let inf = f32::INFINITY;
+        for fault in Fault::ALL {
+            let ctx = Uniform {
+                particles: 5,
+                n_body: 1,
+                fault: fault as u32,
+                ..Default::default()
+            };
+            let mut particles = [
+                particle(Vector2::new(nan, 0.5), Vector2::zeros()),
+                particle(Vector2::new(0.5, 0.3), Vector2::new(inf, 0.0)),
+                Particle {
+                    mass: nan,
+                    ..particle(Vector2::new(0.3, 0.3), Vector2::zeros())
+                },
+                particle(Vector2::new(0.7, 0.3), Vector2::new(1e30, -1e30)),
+                particle(Vector2::new(0.5, 0.8), Vector2::zeros()),
+            ];
+
+            let faults = reference::step(&ctx, &World::default(), &mut particles);
+            assert_finite(&particles);
+            assert_eq!(faults, 4, "{fault:?}");
+
+            // Corrupted particles must not have spread to the healthy one.
+            assert_eq!(particles[4].mass, 1.0);
+
+            match fault {
+                Fault::Remove => assert!(particles[..4].iter().all(|x| x.mass == 0.0)),
+                Fault::Reset => {
+                    // Only the huge but finite velocity can be reset.
+                    assert_eq!(particles[3].position, Vector2::new(0.7, 0.3));
+                    assert_eq!(particles[3].velocity, Vector2::zeros());
+                    assert_eq!(particles[3].mass, 1.0);
+                }
+            }
+
+            for _ in 0..10 {
+                reference::step(&ctx, &World::default(), &mut particles);
+                assert_finite(&particles);
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Runs the same initial state through `compute.wgsl` and the CPU reference
+//! and reports how far the two drift apart.
+
+use anyhow::Result;
+use compute::gpu::Gpu;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    reference,
+    simulation::Simulation,
+    species::Species,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Divergence {
+    pub position: f32,
+    pub velocity: f32,
+}
+
+pub fn seeded_particles(seed: u64, count: u32, species: &[Species]) -> Vec<Particle> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count)
+        .map(|_| Particle::random_with(&mut rng, species))
+        .collect()
+}
+
+pub fn cross_check(
+    gpu: &Gpu,
+    ctx: &Uniform,
+    particles: &[Particle],
+    world: &World,
+    steps: u32,
+    workgroup_size: u32,
+) -> Result<Divergence> {
+    let uniform = gpu.create_uniform(ctx)?;
+    let mut simulation = Simulation::new(gpu, &uniform, particles.to_vec(), world, workgroup_size)?;
+
+    let mut cpu = particles.to_vec();
+    for _ in 0..steps {
+        simulation.step(ctx.particles);
+        reference::step(ctx, world, &mut cpu);
+    }
+
+    Ok(divergence(&simulation.front().download()?, &cpu))
+}
+
+pub fn divergence(a: &[Particle], b: &[Particle]) -> Divergence {
+    a.iter()
+        .zip(b)
+        .fold(Divergence::default(), |acc, (a, b)| Divergence {
+            position: acc.position.max((a.position - b.position).magnitude()),
+            velocity: acc.velocity.max((a.velocity - b.velocity).magnitude()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use compute::gpu::Gpu;
+
+    use super::{cross_check, seeded_particles};
+    use compute::export::nalgebra::Vector2;
+
+    use crate::{
+        charge::PointCharge,
+        potential::Potential,
+        shader::DEFAULT_WORKGROUP_SIZE,
+        species::{Boundary, Species},
+        types::Uniform,
+        world::World,
+    };
+
+    /// Environment variable that turns a missing adapter into a failure.
+    const REQUIRE_GPU: &str = "GRAVITY_REQUIRE_GPU";
+
+    #[test]
+    fn kernel_matches_reference() {
+        let ctx = Uniform {
+            particles: 256,
+            ..Default::default()
+        };
+        check(ctx, &World::default());
+    }
+
+    #[test]
+    fn n_body_kernel_matches_reference() {
+        let ctx = Uniform {
+            particles: 256,
+            n_body: 1,
+            ..Default::default()
+        };
+        check(ctx, &World::default());
+    }
+
+    #[test]
+    fn potentials_match_reference() {
+        let ctx = Uniform {
+            particles: 256,
+            time: 0.5,
+            ..Default::default()
+        };
+        let world = World {
+            potentials: (0..Potential::NAMES.len())
+                .map(Potential::default_of)
+                .collect(),
+            ..Default::default()
+        };
+        check(ctx, &world);
+    }
+
+    #[test]
+    fn species_match_reference() {
+        let ctx = Uniform {
+            particles: 256,
+            ..Default::default()
+        };
+        let world = World {
+            species: Boundary::ALL
+                .into_iter()
+                .zip([[1.0, 1.0], [0.5, -1.0], [-1.0, 0.0], [0.0, 2.0]])
+                .map(|(boundary, response)| Species {
+                    mass: [0.5, 2.0],
+                    response,
+                    boundary,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        check(ctx, &world);
+    }
+
+    #[test]
+    fn charges_match_reference() {
+        let ctx = Uniform {
+            particles: 256,
+            magnetic: 20.0,
+            ..Default::default()
+        };
+        let world = World {
+            species: [1.0, -1.0]
+                .into_iter()
+                .map(|charge| Species {
+                    charge,
+                    ..Default::default()
+                })
+                .collect(),
+            charges: vec![
+                PointCharge {
+                    position: Vector2::new(0.3, 0.3),
+                    charge: 1.0,
+                },
+                PointCharge {
+                    position: Vector2::new(0.7, 0.6),
+                    charge: -2.0,
+                },
+            ],
+            ..Default::default()
+        };
+        check(ctx, &world);
+    }
+
+    /// Runs the kernel for 1000 steps against the reference. `Gpu::init`
+    /// takes no adapter options, so a software adapter such as lavapipe or
+    /// WARP cannot be requested here and is only used when wgpu picks it by
+    /// default. Without any adapter the check is skipped, unless
+    /// `REQUIRE_GPU` is set in the environment to make that a failure.
+    fn check(ctx: Uniform, world: &World) {
+        let gpu = match Gpu::init() {
+            Ok(gpu) => gpu,
+            Err(err) if env::var_os(REQUIRE_GPU).is_some() => {
+                panic!("{REQUIRE_GPU} is set but no GPU adapter is available: {err}")
+            }
+            Err(err) => {
+                eprintln!("skipping GPU cross-check, no adapter ({err}), set {REQUIRE_GPU} to fail instead");
+                return;
+            }
+        };
+
+        let particles = seeded_particles(0, ctx.particles, &world.species);
+        let divergence =
+            cross_check(&gpu, &ctx, &particles, world, 1000, DEFAULT_WORKGROUP_SIZE).unwrap();
+
+        assert!(divergence.position < 1e-3, "{divergence:?}");
+        assert!(divergence.velocity < 1e-2, "{divergence:?}");
+    }
+}
@@ -1,47 +1,110 @@
 use std::time::Instant;
 
 use anyhow::{Ok, Result};
-use compute::{
-    export::{
-        wgpu::{include_wgsl, ShaderStages},
-        winit::window::WindowAttributes,
-    },
-    gpu::Gpu,
-};
+use compute::{export::winit::window::WindowAttributes, gpu::Gpu};
 
+mod analysis;
 mod app;
+mod args;
+mod bench;
+mod charge;
+mod dissipation;
+mod export;
+mod field;
+mod guard;
+mod harness;
+mod pane;
+mod phase;
+mod ping_pong;
+mod plots;
+mod potential;
+mod reference;
+mod session;
+mod shader;
+mod simulation;
+mod snapshot;
+mod species;
+mod sprites;
+mod three_body;
 mod types;
-use crate::types::Uniform;
+mod world;
+use crate::{
+    args::Args,
+    export::Exporter,
+    pane::{Pane, MAX_PANES},
+    plots::Plots,
+    session::Session,
+    types::{Overlay, Uniform},
+    world::World,
+};
 use app::App;
 
 fn main() -> Result<()> {
+    let args = Args::parse()?;
     let gpu = Gpu::init()?;
 
+    if let Some(steps) = args.cross_check {
+        let ctx = Uniform {
+            particles: 1024,
+            ..Default::default()
+        };
+        let world = World::default();
+        let particles = harness::seeded_particles(0, ctx.particles, &world.species);
+        let divergence =
+            harness::cross_check(&gpu, &ctx, &particles, &world, steps, args.workgroup_size)?;
+        println!(
+            "Max divergence after {steps} steps: position {:e}, velocity {:e}",
+            divergence.position, divergence.velocity
+        );
+        return Ok(());
+    }
+
+    if args.bench {
+        return bench::run(&gpu, args.workgroup_size);
+    }
+
+    if let Some(path) = &args.replay {
+        let session = Session::load(path)?;
+        let snapshot = if args.cpu {
+            session.replay_reference()
+        } else {
+            session.replay(&gpu, args.workgroup_size)?
+        };
+        snapshot.save(&args.output)?;
+        println!(
+            "Replayed {} steps, final state written to {}",
+            session.steps,
+            args.output.display()
+        );
+        return Ok(());
+    }
+
     let ctx = Uniform::default();
-    let uniform = gpu.create_uniform(&ctx)?;
-    let dots = gpu.create_storage(Vec::new())?;
-
-    let render = gpu
-        .render_pipeline(include_wgsl!("../shaders/render.wgsl"))
-        .bind_buffer(&dots, ShaderStages::VERTEX_FRAGMENT)
-        .bind_buffer(&uniform, ShaderStages::VERTEX_FRAGMENT)
-        .finish();
-    let compute = gpu
-        .compute_pipeline(include_wgsl!("../shaders/compute.wgsl"))
-        .bind_buffer(&dots)
-        .bind_buffer(&uniform)
-        .finish();
+    let world = World::default();
+    let panes = (0..MAX_PANES)
+        .map(|_| Pane::new(&gpu, &ctx, &world, args.workgroup_size))
+        .collect::<Result<Vec<_>>>()?;
 
     gpu.create_window(
         WindowAttributes::default().with_title("Dots Example"),
         App {
-            render,
-            compute,
+            panes,
+            pane_count: 1,
+            selected: 0,
+            sync: true,
+            seed: 0,
+            divergence: None,
 
-            ctx,
-            uniform,
-            dots,
+            world,
+            snapshot_path: "snapshot.json".into(),
+            recorder: None,
+            session_path: "session.json".into(),
+            overlay_ctx: Overlay::default(),
+            plots: Plots::default(),
+            exporter: Exporter::default(),
+            sample_interval: 10,
 
+            frame: 0,
             last_frame: Instant::now(),
         },
     )
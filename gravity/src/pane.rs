@@ -0,0 +1,177 @@
+//! An independent simulation drawn into its own part of the window, so
+//! parameter changes can be compared side by side from the same start.
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use compute::{
+    buffer::{StorageBuffer, UniformBuffer},
+    export::{
+        nalgebra::Vector2,
+        wgpu::{RenderPass, ShaderStages},
+    },
+    gpu::Gpu,
+    misc::mutability::Immutable,
+    pipeline::render::RenderPipeline,
+};
+
+use crate::{
+    shader,
+    simulation::Simulation,
+    sprites::Sprites,
+    three_body,
+    types::{Overlay, Particle, Uniform},
+    world::World,
+};
+
+/// Panes are created up front, as the app has no `Gpu` to make more later.
+pub const MAX_PANES: usize = 4;
+/// Least time between downloads of the fault count, which stall the GPU.
+const FAULT_REFRESH: Duration = Duration::from_millis(500);
+
+pub struct Pane {
+    pub ctx: Uniform,
+    pub uniform: UniformBuffer<Uniform>,
+    pub simulation: Simulation,
+    pub sprites: Sprites,
+    /// Last count read from `simulation.faults`, and when.
+    faults: u32,
+    faults_read: Option<Instant>,
+
+    overlay: RenderPipeline,
+    /// Per pane, as the Lagrange points depend on the pane's attractors.
+    overlay_buffer: StorageBuffer<Overlay, Immutable>,
+}
+
+impl Pane {
+    pub fn new(gpu: &Gpu, ctx: &Uniform, world: &World, workgroup_size: u32) -> Result<Self> {
+        let uniform = gpu.create_uniform(ctx)?;
+        let simulation = Simulation::new(gpu, &uniform, Vec::new(), world, workgroup_size)?;
+        let sprites = Sprites::new(gpu, &uniform, &simulation)?;
+
+        let overlay_buffer = gpu.create_storage_read(Overlay::default())?;
+        let overlay = gpu
+            .render_pipeline(shader::overlay_shader())
+            .bind_buffer(&uniform, ShaderStages::FRAGMENT)
+            .bind_buffer(&overlay_buffer, ShaderStages::FRAGMENT)
+            .bind_buffer(&simulation.potentials, ShaderStages::FRAGMENT)
+            .finish();
+
+        Ok(Self {
+            ctx: ctx.clone(),
+            uniform,
+            simulation,
+            sprites,
+            faults: 0,
+            faults_read: None,
+
+            overlay,
+            overlay_buffer,
+        })
+    }
+
+    /// Takes every parameter from `ctx` but keeps this pane's viewport.
+    pub fn sync(&mut self, ctx: &Uniform) {
+        self.ctx = Uniform {
+            window: self.ctx.window,
+            origin: self.ctx.origin,
+            ..ctx.clone()
+        };
+    }
+
+    /// Uploads the uniform, draws the overlay underneath the particles and
+    /// steps the simulation. The render pass viewport must already be set.
+    pub fn step(&mut self, overlay: &mut Overlay, render_pass: &mut RenderPass) {
+        self.uniform.upload(&self.ctx).unwrap();
+        if overlay.visible() {
+            overlay.points = three_body::lagrange_points(&self.ctx);
+            self.overlay_buffer.upload(overlay).unwrap();
+            self.overlay.draw_quad(render_pass, 0..1);
+        }
+
+        self.simulation.step(self.ctx.particles);
+        self.ctx.time += self.ctx.dt;
+    }
+
+    pub fn draw(&mut self, render_pass: &mut RenderPass) {
+        let front = self.simulation.state.front();
+        self.sprites.draw(&self.ctx, front, render_pass);
+    }
+
+    /// Count of non-finite particles, read back at most every
+    /// `FAULT_REFRESH`.
+    pub fn faults(&mut self) -> Result<u32> {
+        if self
+            .faults_read
+            .is_none_or(|x| x.elapsed() >= FAULT_REFRESH)
+        {
+            self.faults = self.simulation.faults()?;
+            self.faults_read = Some(Instant::now());
+        }
+        Ok(self.faults)
+    }
+
+    pub fn clear_faults(&mut self) -> Result<()> {
+        self.simulation.clear_faults()?;
+        self.faults = 0;
+        Ok(())
+    }
+
+    pub fn particles(&self) -> Result<Vec<Particle>> {
+        let mut particles = self.simulation.front().download()?;
+        particles.truncate(self.ctx.particles as usize);
+        Ok(particles)
+    }
+
+    pub fn contains(&self, pointer: Vector2<f32>) -> bool {
+        let local = pointer - self.ctx.origin;
+        (0..2).all(|i| local[i] >= 0.0 && local[i] < self.ctx.window[i])
+    }
+}
+
+/// Origin and size of pane `index` when `count` panes are tiled across
+/// `window` in as square a grid as possible, filled row by row.
+pub fn viewport(window: Vector2<f32>, index: usize, count: usize) -> [Vector2<f32>; 2] {
+    let columns = (count as f32).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+
+    let size = Vector2::new(
+        (window.x / columns as f32).floor(),
+        (window.y / rows as f32).floor(),
+    );
+    let cell = Vector2::new((index % columns) as f32, (index / columns) as f32);
+    [cell.component_mul(&size), size]
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{viewport, MAX_PANES};
+
+    #[test]
+    fn viewports_tile_window() {
+        let window = Vector2::new(1600.0, 900.0);
+        assert_eq!(viewport(window, 0, 1), [Vector2::zeros(), window]);
+
+        for count in 1..=MAX_PANES {
+            let viewports = (0..count)
+                .map(|i| viewport(window, i, count))
+                .collect::<Vec<_>>();
+
+            for (i, [origin, size]) in viewports.iter().enumerate() {
+                assert!(origin.x + size.x <= window.x && origin.y + size.y <= window.y);
+                for [other, _] in &viewports[i + 1..] {
+                    assert_ne!(origin, other);
+                }
+            }
+
+            let area = viewports.iter().map(|[_, x]| x.x * x.y).sum::<f32>();
+            assert!(area <= window.x * window.y);
+        }
+
+        let [origin, size] = viewport(window, 3, 4);
+        assert_eq!(origin, Vector2::new(800.0, 450.0));
+        assert_eq!(size, Vector2::new(800.0, 450.0));
+    }
+}
@@ -0,0 +1,292 @@
+//! Poincaré sections and per-particle phase-space quantities.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::{
+    charge,
+    potential::Field,
+    three_body,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+/// Slack on the distance a particle may cover in a step before the step is
+/// taken as a jump, like wrapping at a periodic boundary, in world units.
+const JUMP_TOLERANCE: f32 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// The line `axis = value`, crossed in the positive (`upward`) or negative
+/// direction. Crossings are plotted in the other coordinate and its velocity,
+/// so `y = 0.5` with `vy > 0` gives points in `(x, vx)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Section {
+    pub axis: Axis,
+    pub value: f32,
+    pub upward: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    X,
+    Y,
+    Vx,
+    Vy,
+    R,
+    Vr,
+    Vt,
+    Energy,
+}
+
+impl Section {
+    fn split(&self, v: Vector2<f32>) -> (f32, f32) {
+        match self.axis {
+            Axis::X => (v.x, v.y),
+            Axis::Y => (v.y, v.x),
+        }
+    }
+
+    /// Where a particle moving from `prev` to `next` over `dt` crossed the
+    /// section, as `(coordinate, velocity)` along the section line. Moving
+    /// further than its speed allows is a jump rather than a crossing.
+    pub fn crossing(&self, prev: &Particle, next: &Particle, dt: f32) -> Option<[f32; 2]> {
+        let speed = prev.velocity.magnitude().max(next.velocity.magnitude());
+        if (next.position - prev.position).magnitude() > speed * dt + JUMP_TOLERANCE {
+            return None;
+        }
+
+        let (a, prev_pos) = self.split(prev.position);
+        let (b, next_pos) = self.split(next.position);
+        let (a, b) = (a - self.value, b - self.value);
+
+        let crossed = match self.upward {
+            true => a < 0.0 && b >= 0.0,
+            false => a > 0.0 && b <= 0.0,
+        };
+        if !crossed {
+            return None;
+        }
+
+        let t = a / (a - b);
+        let prev_vel = self.split(prev.velocity).1;
+        let next_vel = self.split(next.velocity).1;
+        Some([
+            prev_pos + (next_pos - prev_pos) * t,
+            prev_vel + (next_vel - prev_vel) * t,
+        ])
+    }
+
+    pub fn crossings<'a>(
+        &'a self,
+        prev: &'a [Particle],
+        next: &'a [Particle],
+        dt: f32,
+    ) -> impl Iterator<Item = [f32; 2]> + 'a {
+        prev.iter()
+            .zip(next)
+            .filter_map(move |(prev, next)| self.crossing(prev, next, dt))
+    }
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        Self {
+            axis: Axis::Y,
+            value: 0.5,
+            upward: true,
+        }
+    }
+}
+
+impl Quantity {
+    pub const ALL: [Quantity; 8] = [
+        Quantity::X,
+        Quantity::Y,
+        Quantity::Vx,
+        Quantity::Vy,
+        Quantity::R,
+        Quantity::Vr,
+        Quantity::Vt,
+        Quantity::Energy,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quantity::X => "x",
+            Quantity::Y => "y",
+            Quantity::Vx => "vx",
+            Quantity::Vy => "vy",
+            Quantity::R => "r",
+            Quantity::Vr => "v_r",
+            Quantity::Vt => "v_t",
+            Quantity::Energy => "Energy",
+        }
+    }
+
+    /// Radii and velocity components are taken about the attractors'
+    /// barycenter. Energy is per unit mass and includes the background
+    /// potentials, with the attractors weighted by the particle's species and
+    /// the electrostatic energy of charged species.
+    pub fn eval(&self, ctx: &Uniform, world: &World, particle: &Particle) -> f32 {
+        let delta = particle.position - three_body::barycenter(ctx);
+        let radial = delta.try_normalize(0.0).unwrap_or_else(Vector2::x);
+        let tangent = Vector2::new(-radial.y, radial.x);
+
+        match self {
+            Quantity::X => particle.position.x,
+            Quantity::Y => particle.position.y,
+            Quantity::Vx => particle.velocity.x,
+            Quantity::Vy => particle.velocity.y,
+            Quantity::R => delta.magnitude(),
+            Quantity::Vr => particle.velocity.dot(&radial),
+            Quantity::Vt => particle.velocity.dot(&tangent),
+            Quantity::Energy => {
+                let species = world.species_of(particle.species);
+                let electric = match species.charge {
+                    0.0 => 0.0,
+                    q => {
+                        q / particle.mass
+                            * ctx.coulomb
+                            * charge::potential(&world.charges, particle.position, ctx.softening)
+                    }
+                };
+
+                0.5 * particle.velocity.norm_squared()
+                    + three_body::potentials(ctx, particle.position)
+                        .into_iter()
+                        .zip(species.response)
+                        .map(|(phi, response)| phi * response)
+                        .sum::<f32>()
+                    + world.potentials.potential(particle.position, ctx.time)
+                    + electric
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use compute::export::nalgebra::Vector2;
+
+    use super::Section;
+    use crate::{
+        potential::{Harmonic, Potential},
+        reference,
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    const CENTER: Vector2<f32> = Vector2::new(0.5, 0.5);
+
+    fn circular(radius: f32, omega: f32, t: f32) -> Particle {
+        let (sin, cos) = (omega * t).sin_cos();
+        Particle {
+            position: CENTER + Vector2::new(cos, sin) * radius,
+            velocity: Vector2::new(-sin, cos) * radius * omega,
+            mass: 1.0,
+            species: 0,
+        }
+    }
+
+    #[test]
+    fn circular_orbit_crosses_once_per_period() {
+        let (radius, omega, dt) = (0.2, 3.0, 0.001);
+        let section = Section::default();
+
+        let periods = 5;
+        let steps = (periods as f32 * TAU / omega / dt) as usize;
+        let points = (0..steps)
+            .filter_map(|i| {
+                let prev = circular(radius, omega, i as f32 * dt - 0.1);
+                let next = circular(radius, omega, (i + 1) as f32 * dt - 0.1);
+                section.crossing(&prev, &next, dt)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(points.len(), periods);
+        for [x, vx] in points {
+            assert!((x - (CENTER.x + radius)).abs() < 1e-3, "{x}");
+            assert!(vx.abs() < 1e-2, "{vx}");
+        }
+    }
+
+    #[test]
+    fn downward_crossings_are_on_the_other_side() {
+        let (radius, omega, dt) = (0.2, 3.0, 0.001);
+        let section = Section {
+            upward: false,
+            ..Default::default()
+        };
+
+        let prev = circular(radius, omega, TAU / omega / 2.0 - dt / 2.0);
+        let next = circular(radius, omega, TAU / omega / 2.0 + dt / 2.0);
+        let [x, _] = section.crossing(&prev, &next, dt).unwrap();
+        assert!((x - (CENTER.x - radius)).abs() < 1e-3);
+        assert_eq!(Section::default().crossing(&prev, &next, dt), None);
+    }
+
+    #[test]
+    fn harmonic_orbit_is_periodic() {
+        let omega = 4.0;
+        let ctx = Uniform {
+            dt: 0.0005,
+            particles: 1,
+            attractor_mass: Vector2::zeros(),
+            ..Default::default()
+        };
+        let world = World {
+            potentials: vec![Potential::Harmonic(Harmonic {
+                center: CENTER,
+                omega,
+            })],
+            ..Default::default()
+        };
+
+        let mut particle = [circular(0.2, omega, -0.1)];
+        let mut points = Vec::new();
+        for _ in 0..(6.0 * TAU / omega / ctx.dt) as usize {
+            let prev = particle;
+            reference::step(&ctx, &world, &mut particle);
+            points.extend(Section::default().crossings(&prev, &particle, ctx.dt));
+        }
+
+        assert_eq!(points.len(), 6);
+        for [x, vx] in &points {
+            assert!((x - points[0][0]).abs() < 1e-3, "{points:?}");
+            assert!((vx - points[0][1]).abs() < 1e-2, "{points:?}");
+        }
+    }
+
+    #[test]
+    fn wrapping_is_not_a_crossing() {
+        let dt = 0.001;
+        let velocity = Vector2::new(0.0, 1.0);
+        let particle = |y| Particle {
+            position: Vector2::new(0.3, y),
+            velocity,
+            mass: 1.0,
+            species: 0,
+        };
+
+        // Wrapping from the top edge to the bottom passes y = 0.5 downward.
+        let section = Section {
+            upward: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            section.crossing(&particle(0.9995), &particle(0.0005), dt),
+            None
+        );
+
+        let [x, vx] = Section::default()
+            .crossing(&particle(0.4995), &particle(0.5005), dt)
+            .unwrap();
+        assert_eq!([x, vx], [0.3, 0.0]);
+    }
+}
@@ -0,0 +1,55 @@
+/// Tracks which of a pair of state buffers holds the current particles.
+/// Each step reads from the front buffer, writes into the back buffer and
+/// then swaps them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PingPong {
+    front: usize,
+}
+
+impl PingPong {
+    pub fn front(&self) -> usize {
+        self.front
+    }
+
+    pub fn back(&self) -> usize {
+        1 - self.front
+    }
+
+    pub fn swap(&mut self) {
+        self.front = self.back();
+    }
+
+    /// Returns the `(read, write)` buffer indices for the next step and swaps.
+    pub fn step(&mut self) -> (usize, usize) {
+        let out = (self.front(), self.back());
+        self.swap();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PingPong;
+
+    #[test]
+    fn front_and_back_differ() {
+        let mut state = PingPong::default();
+        for _ in 0..4 {
+            assert_ne!(state.front(), state.back());
+            assert!(state.front() < 2 && state.back() < 2);
+            state.swap();
+        }
+    }
+
+    #[test]
+    fn step_writes_next_front() {
+        let mut state = PingPong::default();
+        let (read, write) = state.step();
+        assert_eq!((read, write), (0, 1));
+        assert_eq!(state.front(), write);
+
+        let (read, write) = state.step();
+        assert_eq!((read, write), (1, 0));
+        assert_eq!(state, PingPong::default());
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use compute::export::egui::{ComboBox, Context, DragValue, Ui, Window};
+use egui_plot::{Plot, PlotPoints, Points};
+
+use crate::{
+    phase::{Axis, Quantity, Section},
+    types::{Particle, Uniform},
+    world::World,
+};
+
+const MAX_CROSSINGS: usize = 100_000;
+
+/// Secondary panel with a Poincaré section and a scatter plot of two
+/// particle quantities.
+pub struct Plots {
+    pub open: bool,
+    pub section: Section,
+    pub scatter: [Quantity; 2],
+
+    crossings: VecDeque<[f64; 2]>,
+    scatter_points: Vec<[f64; 2]>,
+    previous: Vec<Particle>,
+    /// Simulation time of `previous`.
+    previous_time: f32,
+}
+
+impl Plots {
+    /// Records section crossings since the last call and refreshes the
+    /// scatter plot.
+    pub fn record(&mut self, ctx: &Uniform, world: &World, particles: Vec<Particle>) {
+        let elapsed = ctx.time - self.previous_time;
+        for [x, v] in self.section.crossings(&self.previous, &particles, elapsed) {
+            if self.crossings.len() >= MAX_CROSSINGS {
+                self.crossings.pop_front();
+            }
+            self.crossings.push_back([x as f64, v as f64]);
+        }
+
+        let [x, y] = self.scatter;
+        self.scatter_points = particles
+            .iter()
+            .map(|p| [x.eval(ctx, world, p) as f64, y.eval(ctx, world, p) as f64])
+            .collect();
+        self.previous = particles;
+        self.previous_time = ctx.time;
+    }
+
+    /// Forget the last state, for when the particles are replaced or stop
+    /// being recorded.
+    pub fn reset(&mut self) {
+        self.previous.clear();
+    }
+
+    pub fn ui(&mut self, ctx: &Context) {
+        // Not recorded while closed, so the last state would be stale.
+        if !self.open {
+            self.reset();
+        }
+
+        Window::new("Phase Space")
+            .open(&mut self.open)
+            .default_width(300.0)
+            .show(ctx, |ui| {
+                ui.heading("Poincaré Section");
+                section_editor(ui, &mut self.section, &mut self.crossings);
+
+                let (coord, vel) = match self.section.axis {
+                    Axis::X => ("y", "vy"),
+                    Axis::Y => ("x", "vx"),
+                };
+                Plot::new("poincare")
+                    .height(200.0)
+                    .x_axis_label(coord)
+                    .y_axis_label(vel)
+                    .show(ui, |plot| {
+                        let points = self.crossings.iter().copied().collect::<Vec<_>>();
+                        plot.points(Points::new(PlotPoints::new(points)).radius(1.0));
+                    });
+
+                ui.separator();
+                ui.heading("Scatter");
+                ui.horizontal(|ui| {
+                    quantity_picker(ui, "scatter_x", &mut self.scatter[0]);
+                    ui.label("vs");
+                    quantity_picker(ui, "scatter_y", &mut self.scatter[1]);
+                });
+
+                Plot::new("scatter")
+                    .height(200.0)
+                    .x_axis_label(self.scatter[0].name())
+                    .y_axis_label(self.scatter[1].name())
+                    .show(ui, |plot| {
+                        let points = PlotPoints::new(self.scatter_points.clone());
+                        plot.points(Points::new(points).radius(1.0));
+                    });
+            });
+    }
+}
+
+impl Default for Plots {
+    fn default() -> Self {
+        Self {
+            open: false,
+            section: Section::default(),
+            scatter: [Quantity::R, Quantity::Vt],
+
+            crossings: VecDeque::new(),
+            scatter_points: Vec::new(),
+            previous: Vec::new(),
+            previous_time: 0.0,
+        }
+    }
+}
+
+fn section_editor(ui: &mut Ui, section: &mut Section, crossings: &mut VecDeque<[f64; 2]>) {
+    let old = *section;
+    ui.horizontal(|ui| {
+        ComboBox::from_id_salt("section_axis")
+            .selected_text(match section.axis {
+                Axis::X => "x",
+                Axis::Y => "y",
+            })
+            .width(0.0)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut section.axis, Axis::X, "x");
+                ui.selectable_value(&mut section.axis, Axis::Y, "y");
+            });
+        ui.label("=");
+        ui.add(DragValue::new(&mut section.value).speed(0.001));
+        ui.checkbox(&mut section.upward, "Upward");
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(format!("{} crossings", crossings.len()));
+        if ui.button("Clear").clicked() || *section != old {
+            crossings.clear();
+        }
+    });
+}
+
+fn quantity_picker(ui: &mut Ui, id: &str, quantity: &mut Quantity) {
+    ComboBox::from_id_salt(id)
+        .selected_text(quantity.name())
+        .show_ui(ui, |ui| {
+            for option in Quantity::ALL {
+                ui.selectable_value(quantity, option, option.name());
+            }
+        });
+}
@@ -0,0 +1,307 @@
+//! Smooth background potentials, mirrored by `shaders/potential.wgsl`.
+//! Forces are per unit mass (`-∇Φ`) and several potentials add together.
+
+use compute::export::nalgebra::{Vector2, Vector4};
+use encase::ShaderType;
+use serde::{Deserialize, Serialize};
+
+pub trait Field {
+    fn potential(&self, pos: Vector2<f32>, time: f32) -> f32;
+    fn force(&self, pos: Vector2<f32>, time: f32) -> Vector2<f32>;
+}
+
+/// Constant field, `Φ = -g·r`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UniformField {
+    pub field: Vector2<f32>,
+}
+
+/// Isotropic harmonic well, `Φ = ½ω²|r - c|²`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Harmonic {
+    pub center: Vector2<f32>,
+    pub omega: f32,
+}
+
+/// Cored logarithmic halo with a flat rotation curve of speed `v0`,
+/// `Φ = ½v0² ln(rc² + |r - c|²)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LogarithmicHalo {
+    pub center: Vector2<f32>,
+    pub v0: f32,
+    pub core: f32,
+}
+
+/// Quadrupole bar rotating with `pattern_speed`. In the bar frame
+/// `Φ = -A (x² - y²) / (b² + x² + y²)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RotatingBar {
+    pub center: Vector2<f32>,
+    pub strength: f32,
+    pub scale: f32,
+    pub pattern_speed: f32,
+}
+
+/// Miyamoto–Nagai disc evaluated in its midplane,
+/// `Φ = -M / √(R² + (a + b)²)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MiyamotoNagai {
+    pub center: Vector2<f32>,
+    pub mass: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Potential {
+    Uniform(UniformField),
+    Harmonic(Harmonic),
+    LogarithmicHalo(LogarithmicHalo),
+    RotatingBar(RotatingBar),
+    MiyamotoNagai(MiyamotoNagai),
+}
+
+/// Tagged layout shared with the `Potential` struct in `potential.wgsl`.
+#[derive(ShaderType, Debug, Default, Clone, Copy, PartialEq)]
+pub struct GpuPotential {
+    kind: u32,
+    center: Vector2<f32>,
+    params: Vector4<f32>,
+}
+
+impl Field for UniformField {
+    fn potential(&self, pos: Vector2<f32>, _time: f32) -> f32 {
+        -self.field.dot(&pos)
+    }
+
+    fn force(&self, _pos: Vector2<f32>, _time: f32) -> Vector2<f32> {
+        self.field
+    }
+}
+
+impl Field for Harmonic {
+    fn potential(&self, pos: Vector2<f32>, _time: f32) -> f32 {
+        0.5 * self.omega * self.omega * (pos - self.center).norm_squared()
+    }
+
+    fn force(&self, pos: Vector2<f32>, _time: f32) -> Vector2<f32> {
+        -self.omega * self.omega * (pos - self.center)
+    }
+}
+
+impl Field for LogarithmicHalo {
+    fn potential(&self, pos: Vector2<f32>, _time: f32) -> f32 {
+        let r2 = (pos - self.center).norm_squared();
+        0.5 * self.v0 * self.v0 * (self.core * self.core + r2).ln()
+    }
+
+    fn force(&self, pos: Vector2<f32>, _time: f32) -> Vector2<f32> {
+        let delta = pos - self.center;
+        -self.v0 * self.v0 * delta / (self.core * self.core + delta.norm_squared())
+    }
+}
+
+impl RotatingBar {
+    fn rotation(&self, time: f32) -> (f32, f32) {
+        let angle = self.pattern_speed * time;
+        (angle.cos(), angle.sin())
+    }
+}
+
+impl Field for RotatingBar {
+    fn potential(&self, pos: Vector2<f32>, time: f32) -> f32 {
+        let (cos, sin) = self.rotation(time);
+        let delta = pos - self.center;
+        let (x, y) = (cos * delta.x + sin * delta.y, cos * delta.y - sin * delta.x);
+
+        let b2 = self.scale * self.scale;
+        -self.strength * (x * x - y * y) / (b2 + x * x + y * y)
+    }
+
+    fn force(&self, pos: Vector2<f32>, time: f32) -> Vector2<f32> {
+        let (cos, sin) = self.rotation(time);
+        let delta = pos - self.center;
+        let (x, y) = (cos * delta.x + sin * delta.y, cos * delta.y - sin * delta.x);
+
+        let b2 = self.scale * self.scale;
+        let denom = (b2 + x * x + y * y).powi(2);
+        let fx = 2.0 * self.strength * x * (b2 + 2.0 * y * y) / denom;
+        let fy = -2.0 * self.strength * y * (b2 + 2.0 * x * x) / denom;
+
+        Vector2::new(cos * fx - sin * fy, sin * fx + cos * fy)
+    }
+}
+
+impl Field for MiyamotoNagai {
+    fn potential(&self, pos: Vector2<f32>, _time: f32) -> f32 {
+        let ab = self.a + self.b;
+        -self.mass / ((pos - self.center).norm_squared() + ab * ab).sqrt()
+    }
+
+    fn force(&self, pos: Vector2<f32>, _time: f32) -> Vector2<f32> {
+        let ab = self.a + self.b;
+        let delta = pos - self.center;
+        -self.mass * delta / (delta.norm_squared() + ab * ab).powf(1.5)
+    }
+}
+
+impl Potential {
+    pub const NAMES: [&str; 5] = [
+        "Uniform",
+        "Harmonic",
+        "Logarithmic Halo",
+        "Rotating Bar",
+        "Miyamoto–Nagai",
+    ];
+
+    /// Creates a potential of the given kind (an index into [`Self::NAMES`])
+    /// with reasonable default parameters.
+    pub fn default_of(kind: usize) -> Self {
+        let center = Vector2::repeat(0.5);
+        match kind {
+            0 => Self::Uniform(UniformField {
+                field: Vector2::new(0.0, -1.0),
+            }),
+            1 => Self::Harmonic(Harmonic { center, omega: 2.0 }),
+            2 => Self::LogarithmicHalo(LogarithmicHalo {
+                center,
+                v0: 1.0,
+                core: 0.05,
+            }),
+            3 => Self::RotatingBar(RotatingBar {
+                center,
+                strength: 0.5,
+                scale: 0.1,
+                pattern_speed: 5.0,
+            }),
+            _ => Self::MiyamotoNagai(MiyamotoNagai {
+                center,
+                mass: 0.5,
+                a: 0.05,
+                b: 0.01,
+            }),
+        }
+    }
+
+    pub fn kind(&self) -> usize {
+        match self {
+            Self::Uniform(_) => 0,
+            Self::Harmonic(_) => 1,
+            Self::LogarithmicHalo(_) => 2,
+            Self::RotatingBar(_) => 3,
+            Self::MiyamotoNagai(_) => 4,
+        }
+    }
+
+    fn field(&self) -> &dyn Field {
+        match self {
+            Self::Uniform(x) => x,
+            Self::Harmonic(x) => x,
+            Self::LogarithmicHalo(x) => x,
+            Self::RotatingBar(x) => x,
+            Self::MiyamotoNagai(x) => x,
+        }
+    }
+
+    pub fn to_gpu(self) -> GpuPotential {
+        let (center, params) = match self {
+            Self::Uniform(x) => (
+                Vector2::zeros(),
+                Vector4::new(x.field.x, x.field.y, 0.0, 0.0),
+            ),
+            Self::Harmonic(x) => (x.center, Vector4::new(x.omega, 0.0, 0.0, 0.0)),
+            Self::LogarithmicHalo(x) => (x.center, Vector4::new(x.v0, x.core, 0.0, 0.0)),
+            Self::RotatingBar(x) => (
+                x.center,
+                Vector4::new(x.strength, x.scale, x.pattern_speed, 0.0),
+            ),
+            Self::MiyamotoNagai(x) => (x.center, Vector4::new(x.mass, x.a, x.b, 0.0)),
+        };
+
+        GpuPotential {
+            kind: self.kind() as u32,
+            center,
+            params,
+        }
+    }
+}
+
+impl Field for Potential {
+    fn potential(&self, pos: Vector2<f32>, time: f32) -> f32 {
+        self.field().potential(pos, time)
+    }
+
+    fn force(&self, pos: Vector2<f32>, time: f32) -> Vector2<f32> {
+        self.field().force(pos, time)
+    }
+}
+
+impl Field for [Potential] {
+    fn potential(&self, pos: Vector2<f32>, time: f32) -> f32 {
+        self.iter().map(|x| x.potential(pos, time)).sum()
+    }
+
+    fn force(&self, pos: Vector2<f32>, time: f32) -> Vector2<f32> {
+        self.iter().map(|x| x.force(pos, time)).sum()
+    }
+}
+
+pub fn to_gpu(potentials: &[Potential]) -> Vec<GpuPotential> {
+    potentials.iter().map(|x| x.to_gpu()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{Field, Potential};
+
+    const POINTS: [[f32; 2]; 5] = [[0.5, 0.5], [0.3, 0.6], [0.8, 0.45], [0.55, 0.1], [0.1, 0.9]];
+
+    fn assert_force_is_gradient(field: &(impl Field + ?Sized), time: f32) {
+        let h = 1e-3;
+        for [x, y] in POINTS {
+            let pos = Vector2::new(x, y);
+            let dx = Vector2::new(h, 0.0);
+            let dy = Vector2::new(0.0, h);
+
+            let gradient = Vector2::new(
+                field.potential(pos + dx, time) - field.potential(pos - dx, time),
+                field.potential(pos + dy, time) - field.potential(pos - dy, time),
+            ) / (2.0 * h);
+            let force = field.force(pos, time);
+
+            let error = (force + gradient).magnitude();
+            assert!(
+                error < 1e-2 * force.magnitude().max(1.0),
+                "force {force:?} != -∇Φ {:?} at {pos:?}",
+                -gradient
+            );
+        }
+    }
+
+    #[test]
+    fn forces_are_negative_gradients() {
+        for kind in 0..Potential::NAMES.len() {
+            let potential = Potential::default_of(kind);
+            for time in [0.0, 0.3, 1.7] {
+                assert_force_is_gradient(&potential, time);
+            }
+        }
+    }
+
+    #[test]
+    fn combined_force_is_negative_gradient() {
+        let potentials = (0..Potential::NAMES.len())
+            .map(Potential::default_of)
+            .collect::<Vec<_>>();
+        assert_force_is_gradient(&potentials[..], 0.4);
+
+        let pos = Vector2::new(0.3, 0.7);
+        let sum = potentials
+            .iter()
+            .map(|x| x.force(pos, 0.4))
+            .sum::<Vector2<f32>>();
+        assert!((potentials[..].force(pos, 0.4) - sum).magnitude() < 1e-6);
+    }
+}
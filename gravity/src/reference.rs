@@ -0,0 +1,127 @@
+//! CPU mirror of `shaders/compute.wgsl`, used to verify the kernel.
+
+use crate::{
+    charge, dissipation, guard,
+    potential::Field,
+    three_body,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+/// Returns the number of particles that went non-finite and were recovered,
+/// see `guard::recover`.
+pub fn step(ctx: &Uniform, world: &World, particles: &mut [Particle]) -> u32 {
+    let count = (ctx.particles as usize).min(particles.len());
+    let positions = particles[..count]
+        .iter()
+        .map(|x| (x.position, x.mass))
+        .collect::<Vec<_>>();
+    let mut faults = 0;
+
+    for (i, particle) in particles[..count].iter_mut().enumerate() {
+        let species = world.species_of(particle.species);
+        let [a, b] = three_body::attractor_forces(ctx, particle.position);
+        let mut central = a * species.response[0] + b * species.response[1];
+        for potential in &world.potentials {
+            central += potential.force(particle.position, ctx.time);
+        }
+
+        let mut force = (central
+            + three_body::frame_acceleration(ctx, particle.position, particle.velocity)
+            + dissipation::acceleration(ctx, particle.position, particle.velocity, central))
+            * ctx.dt
+            * particle.mass
+            + species.charge
+                * ctx.coulomb
+                * charge::field(&world.charges, particle.position, ctx.softening)
+                * ctx.dt;
+
+        if ctx.n_body != 0 {
+            for (j, &(position, mass)) in positions.iter().enumerate() {
+                // A corrupted particle must not spread to the others.
+                let valid = mass > 0.0 && position.iter().all(|x| x.is_finite());
+                if j != i && valid {
+                    let diff = position - particle.position;
+                    force += three_body::softened_pull(diff, mass, ctx.softening)
+                        * ctx.dt
+                        * ctx.gravity
+                        * particle.mass;
+                }
+            }
+        }
+
+        if particle.mass <= 0.0 {
+            continue;
+        }
+
+        let prev = *particle;
+        let acceleration = force / particle.mass;
+        particle.velocity += acceleration;
+        if ctx.magnetic != 0.0 {
+            let angle = charge::gyration(species.charge, particle.mass, ctx.magnetic, ctx.dt);
+            particle.velocity = three_body::rotate(particle.velocity, angle);
+        }
+        particle.position += particle.velocity * ctx.dt;
+        species.boundary.apply(particle);
+
+        if !guard::is_finite(particle) {
+            faults += 1;
+            *particle = guard::recover(ctx, prev);
+        }
+    }
+
+    faults
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::step;
+    use crate::{
+        species::Species,
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    #[test]
+    fn species_response() {
+        let ctx = Uniform {
+            particles: 4,
+            ..Default::default()
+        };
+        let responses = [[1.0, 1.0], [0.0, 0.0], [-1.0, -1.0], [1.0, 0.0]];
+        let world = World {
+            species: responses
+                .iter()
+                .map(|&response| Species {
+                    response,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        // Left of attractor A, so both attractors pull in +x.
+        let mut particles = (0..4)
+            .map(|species| Particle {
+                position: Vector2::new(0.2, 0.5),
+                velocity: Vector2::zeros(),
+                mass: 1.0,
+                species,
+            })
+            .collect::<Vec<_>>();
+        step(&ctx, &world, &mut particles);
+
+        let pull_a = ctx.dt * ctx.attractor_mass.x / 0.2f32.powi(2);
+        let pull_b = ctx.dt * ctx.attractor_mass.y / 0.4f32.powi(2);
+        let expected = [pull_a + pull_b, 0.0, -pull_a - pull_b, pull_a];
+        for (particle, expected) in particles.iter().zip(expected) {
+            assert!(
+                (particle.velocity - Vector2::new(expected, 0.0)).magnitude() < 1e-6,
+                "{:?} != {expected}",
+                particle.velocity
+            );
+        }
+    }
+}
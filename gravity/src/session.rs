@@ -0,0 +1,349 @@
+//! Records everything that changes the simulation in the interactive app so a
+//! session can be replayed headlessly, see `--replay`.
+
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use compute::{export::nalgebra::Vector2, gpu::Gpu};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    reference,
+    simulation::Simulation,
+    snapshot::Snapshot,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Seeds the generator used for every spawn.
+    pub seed: u64,
+    pub ctx: Uniform,
+    pub world: World,
+    pub events: Vec<Event>,
+    /// Total number of steps taken while recording.
+    pub steps: u64,
+}
+
+/// An action applied before the step with index `step` is taken.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub step: u64,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    /// Any parameter change, including attractor edits.
+    Uniform(Uniform),
+    /// Potentials or species were edited.
+    World(World),
+    /// `ctx.particles` particles were drawn from the session's generator.
+    Spawn,
+    /// Particles replaced wholesale, e.g. by loading a snapshot.
+    Particles(Vec<Particle>),
+}
+
+/// Builds a `Session` from the app's state as it changes.
+pub struct Recorder {
+    session: Session,
+    rng: StdRng,
+
+    /// State as a replay would see it, to detect changes.
+    ctx: Uniform,
+    world: World,
+}
+
+/// Steps through a `Session`, applying its events at the recorded steps.
+pub struct Replay<'a> {
+    session: &'a Session,
+    next: usize,
+    rng: StdRng,
+
+    pub step: u64,
+    pub ctx: Uniform,
+    pub world: World,
+}
+
+/// What `Replay::apply` changed, so the caller can upload it.
+#[derive(Default)]
+pub struct Applied {
+    pub world: bool,
+    pub particles: Option<Vec<Particle>>,
+}
+
+impl Session {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Runs the whole session through the compute kernel.
+    pub fn replay(&self, gpu: &Gpu, workgroup_size: u32) -> Result<Snapshot> {
+        let mut replay = Replay::new(self);
+        let uniform = gpu.create_uniform(&replay.ctx)?;
+        let mut simulation =
+            Simulation::new(gpu, &uniform, Vec::new(), &replay.world, workgroup_size)?;
+
+        while !replay.done() {
+            let applied = replay.apply();
+            if applied.world {
+                simulation.upload_world(&replay.world)?;
+            }
+            if let Some(particles) = applied.particles {
+                simulation.upload(&particles)?;
+            }
+
+            uniform.upload(&replay.ctx)?;
+            simulation.step(replay.ctx.particles);
+            replay.advance();
+        }
+
+        let mut particles = simulation.front().download()?;
+        particles.truncate(replay.ctx.particles as usize);
+        Ok(replay.finish(particles))
+    }
+
+    /// Runs the whole session through the CPU reference.
+    pub fn replay_reference(&self) -> Snapshot {
+        let mut replay = Replay::new(self);
+        let mut particles = Vec::new();
+        while !replay.done() {
+            if let Some(new) = replay.apply().particles {
+                particles = new;
+            }
+            reference::step(&replay.ctx, &replay.world, &mut particles);
+            replay.advance();
+        }
+        replay.finish(particles)
+    }
+}
+
+impl Recorder {
+    pub fn new(seed: u64, ctx: &Uniform, world: &World) -> Self {
+        Self {
+            session: Session {
+                seed,
+                ctx: normalize(ctx),
+                world: world.clone(),
+                events: Vec::new(),
+                steps: 0,
+            },
+            rng: StdRng::seed_from_u64(seed),
+
+            ctx: normalize(ctx),
+            world: world.clone(),
+        }
+    }
+
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Records any change to `ctx` or `world` since the last call. Called
+    /// before every step, and before spawns so they see the same state.
+    pub fn observe(&mut self, ctx: &Uniform, world: &World) {
+        let ctx = normalize(ctx);
+        if ctx != self.ctx {
+            self.ctx = ctx.clone();
+            self.push(Action::Uniform(ctx));
+        }
+        if *world != self.world {
+            self.world = world.clone();
+            self.push(Action::World(world.clone()));
+        }
+    }
+
+    /// Draws `ctx.particles` new particles from the session's generator.
+    pub fn spawn(&mut self, ctx: &Uniform, world: &World) -> Vec<Particle> {
+        self.observe(ctx, world);
+        self.push(Action::Spawn);
+        spawn(&mut self.rng, ctx, world)
+    }
+
+    /// Records particles that did not come from the generator.
+    pub fn replace(&mut self, ctx: &Uniform, world: &World, particles: &[Particle]) {
+        self.observe(ctx, world);
+        self.push(Action::Particles(particles.to_vec()));
+    }
+
+    /// Counts a step taken with the state from the last `observe`.
+    pub fn step(&mut self) {
+        self.session.steps += 1;
+        self.ctx.time += self.ctx.dt;
+    }
+
+    fn push(&mut self, action: Action) {
+        self.session.events.push(Event {
+            step: self.session.steps,
+            action,
+        });
+    }
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(session: &'a Session) -> Self {
+        Self {
+            session,
+            next: 0,
+            rng: StdRng::seed_from_u64(session.seed),
+
+            step: 0,
+            ctx: session.ctx.clone(),
+            world: session.world.clone(),
+        }
+    }
+
+    /// Applies every event recorded for the current step.
+    pub fn apply(&mut self) -> Applied {
+        let mut applied = Applied::default();
+        while let Some(event) = self.session.events.get(self.next) {
+            if event.step > self.step {
+                break;
+            }
+            self.next += 1;
+
+            match &event.action {
+                Action::Uniform(ctx) => self.ctx = ctx.clone(),
+                Action::World(world) => {
+                    self.world = world.clone();
+                    applied.world = true;
+                }
+                Action::Spawn => {
+                    applied.particles = Some(spawn(&mut self.rng, &self.ctx, &self.world));
+                }
+                Action::Particles(particles) => applied.particles = Some(particles.clone()),
+            }
+        }
+        applied
+    }
+
+    /// Moves on to the next step, after the caller has taken this one.
+    pub fn advance(&mut self) {
+        self.ctx.time += self.ctx.dt;
+        self.step += 1;
+    }
+
+    pub fn done(&self) -> bool {
+        self.step >= self.session.steps
+    }
+
+    fn finish(self, particles: Vec<Particle>) -> Snapshot {
+        Snapshot {
+            ctx: self.ctx,
+            world: self.world,
+            particles,
+        }
+    }
+}
+
+fn spawn(rng: &mut StdRng, ctx: &Uniform, world: &World) -> Vec<Particle> {
+    (0..ctx.particles)
+        .map(|_| Particle::random_with(rng, &world.species))
+        .collect()
+}
+
+/// The viewport does not affect the simulation, so it is not recorded.
+fn normalize(ctx: &Uniform) -> Uniform {
+    Uniform {
+        window: Vector2::zeros(),
+        origin: Vector2::zeros(),
+        ..ctx.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{Recorder, Session};
+    use crate::{
+        potential::Potential,
+        reference,
+        species::{Boundary, Species},
+        types::{Particle, Uniform},
+        world::World,
+    };
+
+    /// Plays a fixed script of edits through a recorder while stepping the
+    /// CPU reference, the same way the app drives the GPU.
+    fn scripted() -> (Session, Vec<Particle>) {
+        let mut ctx = Uniform {
+            particles: 100,
+            ..Default::default()
+        };
+        let mut world = World::default();
+        let mut recorder = Recorder::new(42, &ctx, &world);
+        let mut particles = recorder.spawn(&ctx, &world);
+
+        for step in 0..600 {
+            match step {
+                100 => ctx.attractor_a = Vector2::new(0.35, 0.55),
+                150 => ctx.attractor_mass.y = 0.4,
+                200 => {
+                    world.species.push(Species {
+                        response: [1.0, -0.5],
+                        boundary: Boundary::Reflect,
+                        mass: [0.5, 2.0],
+                        ..Default::default()
+                    });
+                    ctx.particles = 150;
+                    particles = recorder.spawn(&ctx, &world);
+                }
+                250 => world.potentials.push(Potential::default_of(1)),
+                300 => {
+                    ctx.n_body = 1;
+                    ctx.linear_drag = 0.5;
+                }
+                400 => {
+                    particles.truncate(50);
+                    ctx.particles = 50;
+                    recorder.replace(&ctx, &world, &particles);
+                }
+                450 => ctx.window = Vector2::new(800.0, 600.0),
+                _ => {}
+            }
+
+            recorder.observe(&ctx, &world);
+            reference::step(&ctx, &world, &mut particles);
+            recorder.step();
+            ctx.time += ctx.dt;
+        }
+
+        (recorder.session().clone(), particles)
+    }
+
+    #[test]
+    fn replay_is_bit_identical() {
+        let (session, expected) = scripted();
+
+        // Round trip through JSON, as `--replay` reads it from disk.
+        let session =
+            serde_json::from_str::<Session>(&serde_json::to_string(&session).unwrap()).unwrap();
+        let replayed = session.replay_reference().particles;
+
+        assert_eq!(replayed.len(), expected.len());
+        for (a, b) in replayed.iter().zip(&expected) {
+            assert_eq!(a.position.map(f32::to_bits), b.position.map(f32::to_bits));
+            assert_eq!(a.velocity.map(f32::to_bits), b.velocity.map(f32::to_bits));
+            assert_eq!(a.mass.to_bits(), b.mass.to_bits());
+            assert_eq!(a.species, b.species);
+        }
+    }
+
+    #[test]
+    fn records_only_changes() {
+        let (session, _) = scripted();
+        let steps = session.events.iter().map(|x| x.step).collect::<Vec<_>>();
+        // Spawn, A, mass B, world + count + spawn, potential, n-body and
+        // drag, count + particles. The window resize is not recorded.
+        assert_eq!(steps, [0, 100, 150, 200, 200, 200, 250, 300, 400, 400]);
+        assert_eq!(session.steps, 600);
+    }
+}
@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+
+use compute::export::wgpu::{ShaderModuleDescriptor, ShaderSource};
+
+pub const DEFAULT_WORKGROUP_SIZE: u32 = 64;
+
+const COMMON: &str = include_str!("../shaders/common.wgsl");
+const POTENTIAL: &str = include_str!("../shaders/potential.wgsl");
+
+/// Builds `compute.wgsl` with the given workgroup size, which also sets the
+/// tile width of the shared-memory n-body loop.
+pub fn compute_shader(workgroup_size: u32) -> ShaderModuleDescriptor<'static> {
+    let header = format!("const workgroup_size: u32 = {workgroup_size};");
+    compose(
+        "compute.wgsl",
+        &[
+            &header,
+            COMMON,
+            POTENTIAL,
+            include_str!("../shaders/compute.wgsl"),
+        ],
+    )
+}
+
+pub fn render_shader() -> ShaderModuleDescriptor<'static> {
+    compose(
+        "render.wgsl",
+        &[COMMON, include_str!("../shaders/render.wgsl")],
+    )
+}
+
+/// Builds `splat.wgsl`, dispatched with the same workgroup size as the kernel.
+pub fn splat_shader(workgroup_size: u32) -> ShaderModuleDescriptor<'static> {
+    let header = format!("const workgroup_size: u32 = {workgroup_size};");
+    compose(
+        "splat.wgsl",
+        &[&header, COMMON, include_str!("../shaders/splat.wgsl")],
+    )
+}
+
+pub fn tonemap_shader() -> ShaderModuleDescriptor<'static> {
+    compose(
+        "tonemap.wgsl",
+        &[COMMON, include_str!("../shaders/tonemap.wgsl")],
+    )
+}
+
+pub fn overlay_shader() -> ShaderModuleDescriptor<'static> {
+    compose(
+        "overlay.wgsl",
+        &[COMMON, POTENTIAL, include_str!("../shaders/overlay.wgsl")],
+    )
+}
+
+/// Number of workgroups needed to cover every particle.
+pub fn workgroups(particles: u32, workgroup_size: u32) -> u32 {
+    particles.div_ceil(workgroup_size)
+}
+
+fn compose(label: &'static str, parts: &[&str]) -> ShaderModuleDescriptor<'static> {
+    ShaderModuleDescriptor {
+        label: Some(label),
+        source: ShaderSource::Wgsl(Cow::Owned(parts.join("\n"))),
+    }
+}
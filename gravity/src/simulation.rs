@@ -0,0 +1,106 @@
+use anyhow::Result;
+use compute::{
+    buffer::{StorageBuffer, UniformBuffer},
+    export::nalgebra::Vector3,
+    gpu::Gpu,
+    misc::mutability::{Immutable, Mutable},
+    pipeline::compute::ComputePipeline,
+};
+
+use crate::{
+    charge::PointCharge,
+    ping_pong::PingPong,
+    potential::GpuPotential,
+    shader,
+    species::GpuSpecies,
+    types::{Particle, Uniform},
+    world::World,
+};
+
+/// Double-buffered particle state along with the compute pipelines that step
+/// it. `compute[i]` reads from `dots[i]` and writes into the other buffer.
+pub struct Simulation {
+    pub dots: [StorageBuffer<Vec<Particle>, Mutable>; 2],
+    pub compute: [ComputePipeline; 2],
+    pub potentials: StorageBuffer<Vec<GpuPotential>, Immutable>,
+    pub species: StorageBuffer<Vec<GpuSpecies>, Immutable>,
+    pub charges: StorageBuffer<Vec<PointCharge>, Immutable>,
+    /// Number of particles that went non-finite and were recovered, see
+    /// `guard.rs`.
+    pub faults: StorageBuffer<u32, Mutable>,
+    pub state: PingPong,
+    pub workgroup_size: u32,
+}
+
+impl Simulation {
+    pub fn new(
+        gpu: &Gpu,
+        uniform: &UniformBuffer<Uniform>,
+        particles: Vec<Particle>,
+        world: &World,
+        workgroup_size: u32,
+    ) -> Result<Self> {
+        let potentials = gpu.create_storage_read(world.gpu_potentials())?;
+        let species = gpu.create_storage_read(world.gpu_species())?;
+        let charges = gpu.create_storage_read(world.charges.clone())?;
+        let faults = gpu.create_storage(0u32)?;
+        let dots = [
+            gpu.create_storage(particles.clone())?,
+            gpu.create_storage(particles)?,
+        ];
+        let compute = [(0, 1), (1, 0)].map(|(read, write)| {
+            gpu.compute_pipeline(shader::compute_shader(workgroup_size))
+                .bind_buffer(&dots[read])
+                .bind_buffer(&dots[write])
+                .bind_buffer(uniform)
+                .bind_buffer(&potentials)
+                .bind_buffer(&species)
+                .bind_buffer(&charges)
+                .bind_buffer(&faults)
+                .finish()
+        });
+
+        Ok(Self {
+            dots,
+            compute,
+            potentials,
+            species,
+            charges,
+            faults,
+            state: PingPong::default(),
+            workgroup_size,
+        })
+    }
+
+    pub fn step(&mut self, particles: u32) {
+        let workgroups = shader::workgroups(particles, self.workgroup_size);
+        let (read, _) = self.state.step();
+        self.compute[read].dispatch(Vector3::new(workgroups, 1, 1));
+    }
+
+    pub fn front(&self) -> &StorageBuffer<Vec<Particle>, Mutable> {
+        &self.dots[self.state.front()]
+    }
+
+    pub fn upload(&self, particles: &[Particle]) -> Result<()> {
+        let particles = particles.to_vec();
+        for dots in &self.dots {
+            dots.upload(&particles)?;
+        }
+        Ok(())
+    }
+
+    pub fn faults(&self) -> Result<u32> {
+        self.faults.download()
+    }
+
+    pub fn clear_faults(&self) -> Result<()> {
+        self.faults.upload(&0)
+    }
+
+    pub fn upload_world(&self, world: &World) -> Result<()> {
+        self.potentials.upload(&world.gpu_potentials())?;
+        self.species.upload(&world.gpu_species())?;
+        self.charges.upload(&world.charges)
+    }
+}
@@ -0,0 +1,28 @@
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    types::{Particle, Uniform},
+    world::World,
+};
+
+/// Full simulation state, saved as JSON.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub ctx: Uniform,
+    pub world: World,
+    pub particles: Vec<Particle>,
+}
+
+impl Snapshot {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+}
@@ -0,0 +1,174 @@
+//! Particle species, mirrored by the `Species` struct in `common.wgsl`.
+
+use compute::export::nalgebra::{Vector2, Vector3};
+use encase::ShaderType;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::types::Particle;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Species {
+    pub name: String,
+    pub color: [f32; 3],
+    /// Relative abundance when spawning particles.
+    pub weight: f32,
+    /// Masses are drawn uniformly from this range.
+    pub mass: [f32; 2],
+    /// Coupling to attractors A and B. One is normal gravity, zero ignores
+    /// the attractor and negative values are repelled like a charge.
+    pub response: [f32; 2],
+    pub boundary: Boundary,
+    /// Charge felt by `World::charges` and the magnetic field.
+    #[serde(default)]
+    pub charge: f32,
+}
+
+/// What happens when a particle leaves the unit square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Boundary {
+    Open,
+    Wrap,
+    Reflect,
+    Absorb,
+}
+
+#[derive(ShaderType, Debug, Clone, Copy, PartialEq)]
+pub struct GpuSpecies {
+    color: Vector3<f32>,
+    response: Vector2<f32>,
+    boundary: u32,
+    charge: f32,
+}
+
+impl Species {
+    pub fn to_gpu(&self) -> GpuSpecies {
+        GpuSpecies {
+            color: Vector3::from(self.color),
+            response: Vector2::from(self.response),
+            boundary: self.boundary as u32,
+            charge: self.charge,
+        }
+    }
+
+    pub fn sample_mass(&self, rng: &mut impl Rng) -> f32 {
+        let [min, max] = self.mass;
+        if max > min {
+            rng.gen_range(min..max)
+        } else {
+            min
+        }
+    }
+
+    /// Picks a species index with probability proportional to its weight.
+    pub fn pick(species: &[Species], rng: &mut impl Rng) -> u32 {
+        let total = species.iter().map(|x| x.weight.max(0.0)).sum::<f32>();
+        let mut target = rng.gen::<f32>() * total;
+        for (i, species) in species.iter().enumerate() {
+            target -= species.weight.max(0.0);
+            if target < 0.0 {
+                return i as u32;
+            }
+        }
+        0
+    }
+}
+
+impl Default for Species {
+    fn default() -> Self {
+        Self {
+            name: "Dust".into(),
+            color: [1.0; 3],
+            weight: 1.0,
+            mass: [1.0, 1.0],
+            response: [1.0, 1.0],
+            boundary: Boundary::Open,
+            charge: 0.0,
+        }
+    }
+}
+
+impl Boundary {
+    pub const ALL: [Boundary; 4] = [
+        Boundary::Open,
+        Boundary::Wrap,
+        Boundary::Reflect,
+        Boundary::Absorb,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Boundary::Open => "Open",
+            Boundary::Wrap => "Wrap",
+            Boundary::Reflect => "Reflect",
+            Boundary::Absorb => "Absorb",
+        }
+    }
+
+    /// Mirrors `apply_boundary` in `common.wgsl`. Absorbed particles are
+    /// marked dead by zeroing their mass.
+    pub fn apply(&self, particle: &mut Particle) {
+        match self {
+            Boundary::Open => {}
+            Boundary::Wrap => {
+                particle.position = particle.position.map(|x| x - x.floor());
+            }
+            Boundary::Reflect => {
+                for i in 0..2 {
+                    if particle.position[i] < 0.0 {
+                        particle.position[i] = -particle.position[i];
+                        particle.velocity[i] = particle.velocity[i].abs();
+                    } else if particle.position[i] > 1.0 {
+                        particle.position[i] = 2.0 - particle.position[i];
+                        particle.velocity[i] = -particle.velocity[i].abs();
+                    }
+                }
+            }
+            Boundary::Absorb => {
+                let pos = particle.position;
+                if pos.x < 0.0 || pos.y < 0.0 || pos.x > 1.0 || pos.y > 1.0 {
+                    particle.mass = 0.0;
+                    particle.velocity = Vector2::zeros();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::Boundary;
+    use crate::types::Particle;
+
+    fn particle(x: f32, y: f32, vx: f32, vy: f32) -> Particle {
+        Particle {
+            position: Vector2::new(x, y),
+            velocity: Vector2::new(vx, vy),
+            mass: 1.0,
+            species: 0,
+        }
+    }
+
+    #[test]
+    fn boundaries() {
+        let mut p = particle(1.25, -0.25, 1.0, -1.0);
+        Boundary::Wrap.apply(&mut p);
+        assert!((p.position - Vector2::new(0.25, 0.75)).magnitude() < 1e-6);
+        assert_eq!(p.velocity, Vector2::new(1.0, -1.0));
+
+        let mut p = particle(1.25, -0.25, 1.0, -1.0);
+        Boundary::Reflect.apply(&mut p);
+        assert!((p.position - Vector2::new(0.75, 0.25)).magnitude() < 1e-6);
+        assert_eq!(p.velocity, Vector2::new(-1.0, 1.0));
+
+        let mut p = particle(1.25, 0.5, 1.0, -1.0);
+        Boundary::Absorb.apply(&mut p);
+        assert_eq!(p.mass, 0.0);
+
+        let mut p = particle(1.25, 0.5, 1.0, -1.0);
+        Boundary::Open.apply(&mut p);
+        assert_eq!(p, particle(1.25, 0.5, 1.0, -1.0));
+    }
+}
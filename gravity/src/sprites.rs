@@ -0,0 +1,126 @@
+//! Draws the particles, either directly as sprites with `render.wgsl` or by
+//! accumulating them with `splat.wgsl` and resolving with `tonemap.wgsl`.
+
+use anyhow::Result;
+use compute::{
+    buffer::{StorageBuffer, UniformBuffer},
+    export::{
+        nalgebra::Vector3,
+        wgpu::{RenderPass, ShaderStages},
+    },
+    gpu::Gpu,
+    misc::mutability::Mutable,
+    pipeline::{compute::ComputePipeline, render::RenderPipeline},
+};
+
+use crate::{shader, simulation::Simulation, types::Uniform};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Antialiased disc of `radius`.
+    Disc,
+    /// Gaussian falloff, blended additively.
+    Glow,
+    /// Disc stretched back along the velocity.
+    Streak,
+    /// A single pixel, regardless of `radius`.
+    Pixel,
+}
+
+pub struct Sprites {
+    /// One pipeline per state buffer, indexed like `simulation.dots`.
+    render: [RenderPipeline; 2],
+    splat: [ComputePipeline; 2],
+    tonemap: RenderPipeline,
+    /// Fixed point RGB per pixel, resized to match the window.
+    accum: StorageBuffer<Vec<u32>, Mutable>,
+    accum_len: usize,
+    workgroup_size: u32,
+}
+
+impl Style {
+    pub const ALL: [Style; 4] = [Style::Disc, Style::Glow, Style::Streak, Style::Pixel];
+
+    pub fn from_u32(style: u32) -> Self {
+        match style {
+            1 => Style::Glow,
+            2 => Style::Streak,
+            3 => Style::Pixel,
+            _ => Style::Disc,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Style::Disc => "Disc",
+            Style::Glow => "Glow",
+            Style::Streak => "Streak",
+            Style::Pixel => "Pixel",
+        }
+    }
+}
+
+impl Sprites {
+    pub fn new(
+        gpu: &Gpu,
+        uniform: &UniformBuffer<Uniform>,
+        simulation: &Simulation,
+    ) -> Result<Self> {
+        let accum = gpu.create_storage(vec![0u32; 3])?;
+
+        let render = simulation.dots.each_ref().map(|dots| {
+            gpu.render_pipeline(shader::render_shader())
+                .bind_buffer(dots, ShaderStages::VERTEX_FRAGMENT)
+                .bind_buffer(uniform, ShaderStages::VERTEX_FRAGMENT)
+                .bind_buffer(&simulation.species, ShaderStages::VERTEX)
+                .finish()
+        });
+        let splat = simulation.dots.each_ref().map(|dots| {
+            gpu.compute_pipeline(shader::splat_shader(simulation.workgroup_size))
+                .bind_buffer(dots)
+                .bind_buffer(uniform)
+                .bind_buffer(&simulation.species)
+                .bind_buffer(&accum)
+                .finish()
+        });
+        let tonemap = gpu
+            .render_pipeline(shader::tonemap_shader())
+            .bind_buffer(uniform, ShaderStages::FRAGMENT)
+            .bind_buffer(&accum, ShaderStages::FRAGMENT)
+            .finish();
+
+        Ok(Self {
+            render,
+            splat,
+            tonemap,
+            accum,
+            accum_len: 3,
+            workgroup_size: simulation.workgroup_size,
+        })
+    }
+
+    /// Draws the particles in buffer `front` with the style set in `ctx`,
+    /// which must already be uploaded.
+    pub fn draw(&mut self, ctx: &Uniform, front: usize, render_pass: &mut RenderPass) {
+        if !accumulate(ctx) {
+            self.render[front].draw_quad(render_pass, 0..ctx.particles);
+            return;
+        }
+
+        let len = 3 * ctx.window.x as usize * ctx.window.y as usize;
+        if len != self.accum_len {
+            self.accum.upload(&vec![0; len]).unwrap();
+            self.accum_len = len;
+        }
+
+        let workgroups = shader::workgroups(ctx.particles, self.workgroup_size);
+        self.splat[front].dispatch(Vector3::new(workgroups, 1, 1));
+        self.tonemap.draw_quad(render_pass, 0..1);
+    }
+}
+
+/// Mirrors `accumulate` in `common.wgsl`. Glow needs additive blending, so it
+/// always goes through the accumulation buffer.
+pub fn accumulate(ctx: &Uniform) -> bool {
+    ctx.hdr != 0 || Style::from_u32(ctx.style) == Style::Glow
+}
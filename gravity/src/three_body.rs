@@ -0,0 +1,221 @@
+//! The two attractors as a restricted three-body problem, mirrors the frame
+//! functions in `shaders/common.wgsl`.
+
+use compute::export::nalgebra::Vector2;
+
+use crate::types::Uniform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    /// Attractors stay where they are placed.
+    Fixed,
+    /// Attractors orbit their barycenter, viewed from the inertial frame.
+    Orbiting,
+    /// Attractors are fixed in a frame rotating with their orbit, adding
+    /// centrifugal and Coriolis terms.
+    Rotating,
+}
+
+impl Frame {
+    pub const ALL: [Frame; 3] = [Frame::Fixed, Frame::Orbiting, Frame::Rotating];
+
+    pub fn from_u32(frame: u32) -> Self {
+        match frame {
+            1 => Frame::Orbiting,
+            2 => Frame::Rotating,
+            _ => Frame::Fixed,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Frame::Fixed => "Fixed",
+            Frame::Orbiting => "Orbiting",
+            Frame::Rotating => "Co-rotating",
+        }
+    }
+}
+
+pub fn angular_velocity(ctx: &Uniform) -> f32 {
+    let d = (ctx.attractor_b - ctx.attractor_a).magnitude();
+    ((ctx.attractor_mass.x + ctx.attractor_mass.y) / (d * d * d)).sqrt()
+}
+
+pub fn barycenter(ctx: &Uniform) -> Vector2<f32> {
+    let m = ctx.attractor_mass;
+    (ctx.attractor_a * m.x + ctx.attractor_b * m.y) / (m.x + m.y)
+}
+
+/// Current positions of the two attractors.
+pub fn attractors(ctx: &Uniform) -> [Vector2<f32>; 2] {
+    let mut out = [ctx.attractor_a, ctx.attractor_b];
+    if Frame::from_u32(ctx.frame) == Frame::Orbiting {
+        let center = barycenter(ctx);
+        let angle = angular_velocity(ctx) * ctx.time;
+        out = out.map(|pos| center + rotate(pos - center, angle));
+    }
+    out
+}
+
+/// Centrifugal and Coriolis acceleration in the co-rotating frame.
+pub fn frame_acceleration(ctx: &Uniform, pos: Vector2<f32>, vel: Vector2<f32>) -> Vector2<f32> {
+    if Frame::from_u32(ctx.frame) != Frame::Rotating {
+        return Vector2::zeros();
+    }
+
+    let omega = angular_velocity(ctx);
+    omega * omega * (pos - barycenter(ctx)) + 2.0 * omega * Vector2::new(vel.y, -vel.x)
+}
+
+/// Gravitational acceleration towards the two attractors.
+pub fn attractor_force(ctx: &Uniform, pos: Vector2<f32>) -> Vector2<f32> {
+    attractor_forces(ctx, pos).into_iter().sum()
+}
+
+/// Acceleration towards each attractor separately, so species can scale them
+/// by their response.
+pub fn attractor_forces(ctx: &Uniform, pos: Vector2<f32>) -> [Vector2<f32>; 2] {
+    let [a, b] = attractors(ctx);
+    [
+        softened_pull(a - pos, ctx.attractor_mass.x, ctx.softening),
+        softened_pull(b - pos, ctx.attractor_mass.y, ctx.softening),
+    ]
+}
+
+/// Plummer softened inverse square pull along `diff`, zero when both `diff`
+/// and the softening are.
+pub fn softened_pull(diff: Vector2<f32>, mass: f32, softening: f32) -> Vector2<f32> {
+    let r2 = diff.norm_squared() + softening * softening;
+    if r2 == 0.0 {
+        return Vector2::zeros();
+    }
+    diff * mass / (r2 * r2.sqrt())
+}
+
+/// Gravitational potential of the two attractors.
+pub fn potential(ctx: &Uniform, pos: Vector2<f32>) -> f32 {
+    potentials(ctx, pos).into_iter().sum()
+}
+
+/// Potential of each attractor separately, see `attractor_forces`.
+pub fn potentials(ctx: &Uniform, pos: Vector2<f32>) -> [f32; 2] {
+    let [a, b] = attractors(ctx);
+    let eps = ctx.softening * ctx.softening;
+    [
+        -ctx.attractor_mass.x / ((pos - a).norm_squared() + eps).sqrt(),
+        -ctx.attractor_mass.y / ((pos - b).norm_squared() + eps).sqrt(),
+    ]
+}
+
+/// Effective potential of the attractors in the frame rotating with them.
+/// Zero-velocity curves are its contours, at `Φ = -C / 2` for Jacobi
+/// constant `C`.
+pub fn effective_potential(ctx: &Uniform, pos: Vector2<f32>) -> f32 {
+    let omega = angular_velocity(ctx);
+    potential(ctx, pos) - 0.5 * omega * omega * (pos - barycenter(ctx)).norm_squared()
+}
+
+/// The five Lagrange points of the attractors at their current positions,
+/// ordered L1 to L5.
+pub fn lagrange_points(ctx: &Uniform) -> [Vector2<f32>; 5] {
+    let [a, b] = attractors(ctx);
+    let mu = ctx.attractor_mass.y / (ctx.attractor_mass.x + ctx.attractor_mass.y);
+
+    let axis = b - a;
+    let normal = Vector2::new(-axis.y, axis.x);
+    let center = barycenter(ctx);
+
+    lagrange_points_normalized(mu as f64).map(|[x, y]| center + axis * x as f32 + normal * y as f32)
+}
+
+/// Lagrange points for mass ratio `mu = m2 / (m1 + m2)`, in units where the
+/// primaries are separated by one, sit at `(-mu, 0)` and `(1 - mu, 0)`, and
+/// the barycenter is the origin.
+pub fn lagrange_points_normalized(mu: f64) -> [[f64; 2]; 5] {
+    let (a, b) = (-mu, 1.0 - mu);
+    let force = |x: f64| {
+        let (ra, rb) = (x - a, x - b);
+        x - (1.0 - mu) * ra / ra.abs().powi(3) - mu * rb / rb.abs().powi(3)
+    };
+
+    // The force goes from -∞ to +∞ across each interval, or the reverse for L3.
+    let eps = 1e-9;
+    let l1 = bisect(force, a + eps, b - eps);
+    let l2 = bisect(force, b + eps, 2.0);
+    let l3 = bisect(force, -2.0, a - eps);
+
+    let x = 0.5 - mu;
+    let y = 3f64.sqrt() / 2.0;
+    [[l1, 0.0], [l2, 0.0], [l3, 0.0], [x, y], [x, -y]]
+}
+
+fn bisect(f: impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> f64 {
+    let rising = f(lo) < f(hi);
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if (f(mid) < 0.0) == rising {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+pub fn rotate(v: Vector2<f32>, angle: f32) -> Vector2<f32> {
+    let (sin, cos) = angle.sin_cos();
+    Vector2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use compute::export::nalgebra::Vector2;
+
+    use super::{effective_potential, lagrange_points, lagrange_points_normalized};
+    use crate::types::Uniform;
+
+    #[test]
+    fn equal_mass_points() {
+        let [l1, l2, l3, l4, l5] = lagrange_points_normalized(0.5);
+        assert!(l1[0].abs() < 1e-9);
+        assert!((l2[0] + l3[0]).abs() < 1e-9);
+
+        for [x, y] in [l4, l5] {
+            assert!(((x + 0.5).hypot(y) - 1.0).abs() < 1e-9);
+            assert!(((x - 0.5).hypot(y) - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn earth_moon_points() {
+        let [l1, l2, l3, ..] = lagrange_points_normalized(0.012_15);
+        assert!((l1[0] - 0.836_9).abs() < 1e-3, "{l1:?}");
+        assert!((l2[0] - 1.155_7).abs() < 1e-3, "{l2:?}");
+        assert!((l3[0] + 1.005_1).abs() < 1e-3, "{l3:?}");
+    }
+
+    #[test]
+    fn points_are_equilibria() {
+        let ctx = Uniform {
+            attractor_mass: Vector2::new(1.0, 0.3),
+            ..Default::default()
+        };
+
+        let h = 1e-4;
+        for point in lagrange_points(&ctx) {
+            let dx = Vector2::new(h, 0.0);
+            let dy = Vector2::new(0.0, h);
+            let gradient = Vector2::new(
+                effective_potential(&ctx, point + dx) - effective_potential(&ctx, point - dx),
+                effective_potential(&ctx, point + dy) - effective_potential(&ctx, point - dy),
+            ) / (2.0 * h);
+
+            // Compare against the scale of a single attractor's pull there.
+            let scale = ctx.attractor_mass.x / (point - ctx.attractor_a).norm_squared();
+            assert!(
+                gradient.magnitude() < 1e-2 * scale,
+                "{point:?}: {gradient:?}"
+            );
+        }
+    }
+}
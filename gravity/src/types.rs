@@ -2,37 +2,98 @@ use std::f32::consts::PI;
 
 use compute::export::nalgebra::Vector2;
 use encase::ShaderType;
-use rand::{thread_rng, Rng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(ShaderType)]
+use crate::species::Species;
+
+#[derive(ShaderType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
 pub struct Particle {
-    position: Vector2<f32>,
-    velocity: Vector2<f32>,
-    mass: f32,
+    pub position: Vector2<f32>,
+    pub velocity: Vector2<f32>,
+    /// Zero for particles absorbed by their species' boundary.
+    pub mass: f32,
+    pub species: u32,
 }
 
-#[derive(ShaderType)]
+#[derive(ShaderType, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
 pub struct Uniform {
+    /// Size and top left corner of the pane being drawn, in pixels.
     pub window: Vector2<f32>,
+    pub origin: Vector2<f32>,
     pub dt: f32,
+    pub time: f32,
 
     pub particles: u32,
     pub radius: f32,
+
+    pub n_body: u32,
+    pub gravity: f32,
+
+    pub attractor_a: Vector2<f32>,
+    pub attractor_b: Vector2<f32>,
+    pub attractor_mass: Vector2<f32>,
+    pub frame: u32,
+
+    /// Dissipation strengths, see `dissipation::acceleration`.
+    pub linear_drag: f32,
+    pub quadratic_drag: f32,
+    pub relaxation: f32,
+
+    /// Strength of the Coulomb force between charged species and
+    /// `World::charges`.
+    pub coulomb: f32,
+    /// Uniform magnetic field along z.
+    pub magnetic: f32,
+
+    /// Plummer softening length of the attractors, n-body and charges.
+    pub softening: f32,
+    /// See `guard::Fault`.
+    pub fault: u32,
+
+    /// See `sprites::Style`.
+    pub style: u32,
+    /// Gaussian width of the glow, relative to the radius.
+    pub glow: f32,
+    /// Seconds of motion covered by a streak.
+    pub streak: f32,
+    /// Accumulate in high dynamic range and tone map.
+    pub hdr: u32,
+    pub exposure: f32,
 }
 
-impl Particle {
-    pub fn random() -> Self {
-        let mut rand = thread_rng();
+/// Extra layers drawn by `overlay.wgsl` underneath the particles.
+#[derive(ShaderType)]
+pub struct Overlay {
+    pub lagrange: u32,
+    pub jacobi: f32,
+    pub points: [Vector2<f32>; 5],
+
+    pub field: u32,
+    pub range: Vector2<f32>,
+    pub contour_spacing: f32,
+    pub arrows: u32,
+    pub arrow_spacing: f32,
+}
 
+impl Particle {
+    pub fn random_with(rand: &mut impl Rng, species: &[Species]) -> Self {
         let t = rand.gen::<f32>() * 2.0 * PI;
 
         let position = Vector2::new(t.sin(), t.cos()) / 4.0 + Vector2::repeat(0.5);
         let velocity = Vector2::new(t.cos(), -t.sin());
 
+        let index = Species::pick(species, rand);
+        let mass = species
+            .get(index as usize)
+            .map_or(1.0, |x| x.sample_mass(rand));
+
         Self {
             position,
             velocity,
-            mass: 1.0,
+            mass,
+            species: index,
         }
     }
 }
@@ -41,10 +102,58 @@ impl Default for Uniform {
     fn default() -> Self {
         Self {
             window: Vector2::zeros(),
+            origin: Vector2::zeros(),
             dt: 0.0001,
+            time: 0.0,
 
             particles: 0,
             radius: 0.001,
+
+            n_body: 0,
+            gravity: 0.00001,
+
+            attractor_a: Vector2::new(0.4, 0.5),
+            attractor_b: Vector2::new(0.6, 0.5),
+            attractor_mass: Vector2::repeat(1.0),
+            frame: 0,
+
+            linear_drag: 0.0,
+            quadratic_drag: 0.0,
+            relaxation: 0.0,
+
+            coulomb: 1.0,
+            magnetic: 0.0,
+
+            softening: 0.001,
+            fault: 0,
+
+            style: 0,
+            glow: 1.0,
+            streak: 0.01,
+            hdr: 0,
+            exposure: 1.0,
+        }
+    }
+}
+
+impl Overlay {
+    pub fn visible(&self) -> bool {
+        self.lagrange != 0 || self.field != 0 || self.arrows != 0
+    }
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self {
+            lagrange: 0,
+            jacobi: 0.0,
+            points: [Vector2::zeros(); 5],
+
+            field: 0,
+            range: Vector2::new(-40.0, 0.0),
+            contour_spacing: 2.0,
+            arrows: 0,
+            arrow_spacing: 40.0,
         }
     }
 }
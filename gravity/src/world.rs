@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    charge::PointCharge,
+    potential::{self, GpuPotential, Potential},
+    species::{GpuSpecies, Species},
+};
+
+/// Everything besides the particles and the uniform that the simulation
+/// reads, kept in sync with the GPU by `Simulation::upload_world`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct World {
+    pub potentials: Vec<Potential>,
+    /// Never empty, particles index into this with `Particle::species`.
+    pub species: Vec<Species>,
+    #[serde(default)]
+    pub charges: Vec<PointCharge>,
+}
+
+impl World {
+    pub fn gpu_potentials(&self) -> Vec<GpuPotential> {
+        potential::to_gpu(&self.potentials)
+    }
+
+    pub fn gpu_species(&self) -> Vec<GpuSpecies> {
+        self.species.iter().map(Species::to_gpu).collect()
+    }
+
+    pub fn species_of(&self, index: u32) -> &Species {
+        &self.species[(index as usize).min(self.species.len() - 1)]
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self {
+            potentials: Vec::new(),
+            species: vec![Species::default()],
+            charges: Vec::new(),
+        }
+    }
+}